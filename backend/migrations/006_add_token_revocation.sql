@@ -0,0 +1,7 @@
+-- Track credential generations so outstanding bearer tokens can be revoked
+ALTER TABLE participants
+    ADD COLUMN token_generation INT NOT NULL DEFAULT 0,
+    ADD COLUMN revoked_at TIMESTAMP WITH TIME ZONE;
+
+COMMENT ON COLUMN participants.token_generation IS 'Bumped on revocation; tokens carrying an older generation are rejected.';
+COMMENT ON COLUMN participants.revoked_at IS 'Set when the creator revokes this participant; blocks re-joining by name.';
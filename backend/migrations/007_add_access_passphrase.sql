@@ -0,0 +1,8 @@
+-- Optional access passphrase (or PIN) for private wentus
+ALTER TABLE wentus ADD COLUMN access_passphrase_hash TEXT;
+
+COMMENT ON COLUMN wentus.access_passphrase_hash IS 'Argon2 PHC hash of the access passphrase. NULL for public wentus.';
+
+-- Failed attempts are counted from the audit log
+CREATE INDEX idx_audit_logs_failed_access ON audit_logs(entity_id, timestamp)
+    WHERE action = 'ACCESS_PASSPHRASE' AND success = FALSE;
@@ -0,0 +1,14 @@
+-- Invite-only wentus: only holders of a single-use invite token may join
+ALTER TABLE wentus ADD COLUMN invite_only BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE invites (
+    id UUID PRIMARY KEY,
+    wentu_id UUID NOT NULL REFERENCES wentus(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    used_at TIMESTAMP WITH TIME ZONE,
+    participant_id UUID REFERENCES participants(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_invites_wentu_id ON invites(wentu_id);
@@ -0,0 +1,4 @@
+-- Correlate audit entries with request logs via the X-Request-Id header
+ALTER TABLE audit_logs ADD COLUMN request_id VARCHAR(128);
+
+CREATE INDEX idx_audit_logs_request_id ON audit_logs(request_id);
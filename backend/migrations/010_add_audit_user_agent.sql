@@ -0,0 +1,2 @@
+-- Record which client made each audited request alongside its IP address
+ALTER TABLE audit_logs ADD COLUMN user_agent VARCHAR(512);
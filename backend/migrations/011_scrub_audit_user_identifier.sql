@@ -0,0 +1,22 @@
+-- audit_logs.user_identifier used to hold raw creator and participant keys. Rewrite entries
+-- to reference the participant by id, and drop any identifier that cannot be mapped (keys of
+-- deleted wentus, wrong keys from failed attempts).
+UPDATE audit_logs a
+SET user_identifier = 'participant:' || p.id
+FROM participants p
+WHERE a.user_identifier = p.id::text;
+
+UPDATE audit_logs a
+SET user_identifier = 'participant:' || p.id
+FROM participants p
+WHERE a.user_identifier = p.participant_key;
+
+UPDATE audit_logs a
+SET user_identifier = 'participant:' || p.id
+FROM wentus w
+JOIN participants p ON p.wentu_id = w.id AND p.is_creator
+WHERE a.user_identifier = w.creator_key;
+
+UPDATE audit_logs
+SET user_identifier = NULL
+WHERE user_identifier IS NOT NULL AND user_identifier NOT LIKE 'participant:%';
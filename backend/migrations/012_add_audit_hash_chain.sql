@@ -0,0 +1,19 @@
+-- Per-wentu hash chain over audit entries: each entry stores its position, the previous
+-- entry's hash and its own hash (see audit_chain::ChainEntry)
+ALTER TABLE audit_logs
+    ADD COLUMN wentu_id UUID,
+    ADD COLUMN chain_seq BIGINT,
+    ADD COLUMN prev_hash VARCHAR(64),
+    ADD COLUMN entry_hash VARCHAR(64);
+
+-- Earlier entries are attributed to their wentu but stay outside the chain
+UPDATE audit_logs SET wentu_id = entity_id WHERE entity_type = 'wentu';
+
+UPDATE audit_logs a
+SET wentu_id = p.wentu_id
+FROM participants p
+WHERE a.entity_type = 'participant' AND a.entity_id = p.id;
+
+CREATE UNIQUE INDEX idx_audit_logs_chain ON audit_logs(wentu_id, chain_seq)
+    WHERE chain_seq IS NOT NULL;
+CREATE INDEX idx_audit_logs_wentu ON audit_logs(wentu_id, timestamp DESC);
@@ -0,0 +1,2 @@
+-- Counts a participant's ballot submissions; returned on ballot receipts
+ALTER TABLE participants ADD COLUMN ballot_version INTEGER NOT NULL DEFAULT 0;
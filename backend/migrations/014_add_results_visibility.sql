@@ -0,0 +1,4 @@
+-- Who may see a wentu's results, and when
+CREATE TYPE results_visibility AS ENUM ('always', 'after_voting', 'after_deadline', 'creator_only');
+
+ALTER TABLE wentus ADD COLUMN results_visibility results_visibility NOT NULL DEFAULT 'always';
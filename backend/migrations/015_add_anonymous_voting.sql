@@ -0,0 +1,19 @@
+-- Anonymous wentus keep ballots unlinked from participants
+ALTER TABLE wentus ADD COLUMN anonymous BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Ballots of anonymous wentus, keyed by a random ballot id instead of the participant.
+-- Deliberately no timestamp, so ballots cannot be matched to votes in the audit log.
+CREATE TABLE anonymous_rankings (
+    ballot_id UUID NOT NULL,
+    wentu_id UUID NOT NULL REFERENCES wentus(id) ON DELETE CASCADE,
+    date_option_id UUID NOT NULL REFERENCES date_ranges(id) ON DELETE CASCADE,
+    preference_order INT NOT NULL,
+    PRIMARY KEY (ballot_id, date_option_id)
+);
+
+CREATE INDEX idx_anonymous_rankings_wentu_id ON anonymous_rankings(wentu_id);
+
+-- ballot_version > 0 marks a participant as having voted, in both modes; count ballots
+-- submitted before versions were tracked
+UPDATE participants p SET ballot_version = 1
+WHERE ballot_version = 0 AND EXISTS (SELECT 1 FROM rankings r WHERE r.participant_id = p.id);
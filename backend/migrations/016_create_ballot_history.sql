@@ -0,0 +1,26 @@
+-- Every version of every identified ballot, so preference shifts and past results can be
+-- reconstructed. Anonymous ballots have no history.
+CREATE TABLE ballot_history (
+    participant_id UUID NOT NULL REFERENCES participants(id) ON DELETE CASCADE,
+    wentu_id UUID NOT NULL REFERENCES wentus(id) ON DELETE CASCADE,
+    version INT NOT NULL,
+    recorded_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- Date option ids, most preferred first
+    ranking UUID[] NOT NULL,
+    PRIMARY KEY (participant_id, version)
+);
+
+CREATE INDEX idx_ballot_history_wentu_recorded ON ballot_history(wentu_id, recorded_at);
+
+-- Seed with current ballots, dated by their last recorded submission
+INSERT INTO ballot_history (participant_id, wentu_id, version, recorded_at, ranking)
+SELECT p.id, p.wentu_id, p.ballot_version,
+       COALESCE(
+           (SELECT MAX(a.timestamp) FROM audit_logs a
+            WHERE a.action = 'UPDATE_PREFERENCES' AND a.success
+              AND a.entity_type = 'participant' AND a.entity_id = p.id),
+           p.joined_at),
+       ARRAY(SELECT r.date_option_id FROM rankings r
+             WHERE r.participant_id = p.id ORDER BY r.preference_order)
+FROM participants p
+WHERE EXISTS (SELECT 1 FROM rankings r WHERE r.participant_id = p.id);
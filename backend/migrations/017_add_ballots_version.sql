@@ -0,0 +1,3 @@
+-- Bumped whenever any ballot in the wentu changes; cached results are only served for the
+-- version they were counted at
+ALTER TABLE wentus ADD COLUMN ballots_version BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Participants authenticate with signed bearer tokens; the per-participant key is never read
+DROP INDEX IF EXISTS idx_participants_key;
+ALTER TABLE participants DROP COLUMN participant_key;
@@ -0,0 +1,3 @@
+-- The creator authenticates with a creator-role bearer token; the wentu-wide key was never
+-- needed after creation and is no longer returned to anyone
+ALTER TABLE wentus DROP COLUMN creator_key;
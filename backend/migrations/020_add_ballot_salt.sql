@@ -0,0 +1,4 @@
+-- Keys the published ballot ids. Kept per wentu rather than derived from TOKEN_SECRET so ids
+-- and receipts survive a secret rotation; existing wentus get one on upgrade
+ALTER TABLE wentus ADD COLUMN ballot_salt TEXT NOT NULL
+    DEFAULT replace(gen_random_uuid()::text || gen_random_uuid()::text, '-', '');
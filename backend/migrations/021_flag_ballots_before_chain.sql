@@ -0,0 +1,12 @@
+-- Ballots stored before audit chaining (012) have no digest to verify against. Flag them
+-- once here, so `audit/verify` can tell them apart from ballots that bypassed the API later
+ALTER TABLE participants ADD COLUMN ballot_predates_chain BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE participants p SET ballot_predates_chain = TRUE
+WHERE EXISTS (SELECT 1 FROM rankings r WHERE r.participant_id = p.id)
+  AND NOT EXISTS (
+      SELECT 1 FROM audit_logs a
+      WHERE a.action = 'UPDATE_PREFERENCES' AND a.success
+        AND a.entity_type = 'participant' AND a.entity_id = p.id
+        AND a.chain_seq IS NOT NULL
+  );
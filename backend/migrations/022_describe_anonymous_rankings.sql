@@ -0,0 +1,4 @@
+-- 015 said leaving out a timestamp keeps anonymous ballots from being matched to votes. It
+-- only keeps the API and audit log from doing so; anyone reading the database itself can
+-- still order ballots by transaction id or physical position
+COMMENT ON TABLE anonymous_rankings IS 'Ballots of anonymous wentus under random ids, with no participant or timestamp. Unlinkable through the API and audit log only: transaction ids and row order still follow submission order.';
@@ -0,0 +1,210 @@
+use argon2::password_hash::{
+    rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+};
+use argon2::Argon2;
+use axum::http::{HeaderMap, StatusCode};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::audit;
+use crate::auth::{bearer_token, check_not_revoked, Role};
+use crate::handlers::AppState;
+use crate::models::ResultsVisibility;
+
+/// Header carrying the access passphrase for protected wentus.
+pub const PASSPHRASE_HEADER: &str = "x-wentu-passphrase";
+
+pub fn hash_passphrase(passphrase: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)?
+        .to_string())
+}
+
+fn verify_passphrase(passphrase: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(passphrase.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Gate access to a passphrase-protected wentu.
+///
+/// Public wentus and callers holding a valid token for the wentu pass straight through;
+/// everyone else must send the passphrase in the `X-Wentu-Passphrase` header.
+pub async fn require_access(
+    state: &AppState,
+    wentu_id: Uuid,
+    slug: &str,
+    passphrase_hash: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<(), StatusCode> {
+    let Some(passphrase_hash) = passphrase_hash else {
+        return Ok(());
+    };
+
+    // Participants were admitted when they joined
+    if let Some(claims) = bearer_token(headers).and_then(|token| state.tokens.verify(token).ok()) {
+        if claims.wentu_id == wentu_id && check_not_revoked(&state.db, &claims).await.is_ok() {
+            return Ok(());
+        }
+    }
+
+    let passphrase = headers
+        .get(PASSPHRASE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?
+        .to_string();
+
+    let client = audit::client_ip().unwrap_or_default();
+    let Some(attempt) = state.limits.passphrase.reserve(&client, wentu_id) else {
+        tracing::warn!("Passphrase attempts throttled for wentu {}", slug);
+        return Err(StatusCode::TOO_MANY_REQUESTS);
+    };
+
+    // Argon2 is deliberately slow; keep it off the async workers
+    let hash = passphrase_hash.to_string();
+    let valid = tokio::task::spawn_blocking(move || verify_passphrase(&passphrase, &hash))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if !valid {
+        tracing::warn!("Wrong passphrase for wentu {}", slug);
+        audit::log_action(
+            &state.db,
+            "ACCESS_PASSPHRASE",
+            "wentu",
+            Some(wentu_id),
+            None,
+            Some(json!({ "slug": slug })),
+            false,
+        )
+        .await;
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    state.limits.passphrase.release(&client, wentu_id, attempt);
+    Ok(())
+}
+
+/// A wentu's results visibility setting with the state it depends on.
+#[derive(Debug, Clone, Copy)]
+pub struct ResultsPolicy {
+    pub visibility: ResultsVisibility,
+    /// Closed early by the creator, or expired
+    pub closed: bool,
+    pub pref_deadline: DateTime<Utc>,
+}
+
+impl ResultsPolicy {
+    /// Whether anyone who can see the wentu can see its results.
+    pub fn is_public(&self, now: DateTime<Utc>) -> bool {
+        match self.visibility {
+            ResultsVisibility::Always => true,
+            ResultsVisibility::AfterDeadline => self.closed || now >= self.pref_deadline,
+            ResultsVisibility::AfterVoting | ResultsVisibility::CreatorOnly => false,
+        }
+    }
+
+    /// When the results open to everyone, if that is still scheduled.
+    pub fn public_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.visibility {
+            ResultsVisibility::AfterDeadline if !self.is_public(now) => Some(self.pref_deadline),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the caller may see the wentu's results under its visibility policy. Results
+/// that are not public yet need the caller's token: the creator always qualifies, other
+/// participants only under `after_voting` once they have submitted a ballot.
+pub async fn can_view_results(
+    state: &AppState,
+    wentu_id: Uuid,
+    policy: ResultsPolicy,
+    headers: &HeaderMap,
+) -> Result<bool, StatusCode> {
+    if policy.is_public(Utc::now()) {
+        return Ok(true);
+    }
+
+    let Some(claims) = bearer_token(headers).and_then(|token| state.tokens.verify(token).ok())
+    else {
+        return Ok(false);
+    };
+    if claims.wentu_id != wentu_id || check_not_revoked(&state.db, &claims).await.is_err() {
+        return Ok(false);
+    }
+    if claims.role == Role::Creator {
+        return Ok(true);
+    }
+    if policy.visibility != ResultsVisibility::AfterVoting {
+        return Ok(false);
+    }
+
+    let ballot_version = sqlx::query_scalar::<_, i32>(
+        "SELECT ballot_version FROM participants WHERE id = $1 AND wentu_id = $2",
+    )
+    .bind(claims.participant_id)
+    .bind(wentu_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(ballot_version.is_some_and(|version| version > 0))
+}
+
+/// Refuse with 403 unless the caller may see the wentu's results.
+pub async fn require_results_visible(
+    state: &AppState,
+    wentu_id: Uuid,
+    policy: ResultsPolicy,
+    headers: &HeaderMap,
+) -> Result<(), StatusCode> {
+    if can_view_results(state, wentu_id, policy, headers).await? {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_results_policy_schedule() {
+        let now = Utc::now();
+        let policy = |visibility, closed, pref_deadline| ResultsPolicy {
+            visibility,
+            closed,
+            pref_deadline,
+        };
+        let tomorrow = now + Duration::days(1);
+
+        let pending = policy(ResultsVisibility::AfterDeadline, false, tomorrow);
+        assert!(!pending.is_public(now));
+        assert_eq!(pending.public_at(now), Some(tomorrow));
+
+        // Closing early or reaching the deadline opens the results
+        let closed = policy(ResultsVisibility::AfterDeadline, true, tomorrow);
+        assert!(closed.is_public(now));
+        assert_eq!(closed.public_at(now), None);
+        assert!(pending.is_public(tomorrow));
+
+        assert!(policy(ResultsVisibility::Always, false, tomorrow).is_public(now));
+        for visibility in [
+            ResultsVisibility::AfterVoting,
+            ResultsVisibility::CreatorOnly,
+        ] {
+            let never = policy(visibility, true, now - Duration::days(1));
+            assert!(!never.is_public(now));
+            assert_eq!(never.public_at(now), None);
+        }
+    }
+}
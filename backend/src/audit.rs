@@ -1,30 +1,298 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::header::USER_AGENT,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{SubsecRound, Utc};
 use serde_json::Value;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
+use std::net::SocketAddr;
 use uuid::Uuid;
 
-/// Persist an audit log entry; best-effort (errors are logged but not bubbled).
+use crate::audit_chain::{self, ChainEntry};
+use crate::handlers::AppState;
+use crate::telemetry::REQUEST_ID_HEADER;
+
+/// Longest user agent stored, matching the `audit_logs.user_agent` column.
+const MAX_USER_AGENT_LEN: usize = 512;
+
+/// Who performed an audited action, stored in `audit_logs.user_identifier`. Only built from
+/// a participant id, so credentials cannot end up in the audit table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Actor(String);
+
+impl Actor {
+    pub fn participant(participant_id: Uuid) -> Self {
+        Self(format!("participant:{}", participant_id))
+    }
+}
+
+/// Per-request details attached to every audit entry written while handling the request.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub request_id: Option<String>,
+    /// Client address, taken from forwarding headers only when the peer is a trusted proxy
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+tokio::task_local! {
+    static CONTEXT: AuditContext;
+}
+
+/// Middleware making the request's audit context available to `log_action`. Must run inside
+/// the layer that assigns `X-Request-Id`.
+pub async fn scope_context(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let headers = req.headers();
+    let context = AuditContext {
+        request_id: headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        ip_address: req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| {
+                state
+                    .settings
+                    .trusted_proxies
+                    .client_ip(peer.ip(), headers)
+                    .to_string()
+            }),
+        user_agent: headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|agent| agent.chars().take(MAX_USER_AGENT_LEN).collect()),
+    };
+    CONTEXT.scope(context, next.run(req)).await
+}
+
+/// Client address of the request being handled, as recorded on its audit entries.
+pub fn client_ip() -> Option<String> {
+    CONTEXT
+        .try_with(|context| context.ip_address.clone())
+        .ok()
+        .flatten()
+}
+
+/// Context of the request being handled; empty for background tasks.
+fn current_context() -> AuditContext {
+    CONTEXT.try_with(AuditContext::clone).unwrap_or_default()
+}
+
+/// Persist an audit log entry; best-effort (errors are logged but not bubbled). Entries
+/// about a wentu or one of its participants are appended to that wentu's hash chain.
 pub async fn log_action(
     db: &PgPool,
     action: &str,
     entity_type: &str,
     entity_id: Option<Uuid>,
-    user_identifier: Option<&str>,
+    actor: Option<Actor>,
     details: Option<Value>,
     success: bool,
 ) {
-    if let Err(err) = sqlx::query(
-        "INSERT INTO audit_logs (action, entity_type, entity_id, user_identifier, details, success)
-         VALUES ($1, $2, $3, $4, $5, $6)",
-    )
-    .bind(action)
-    .bind(entity_type)
-    .bind(entity_id)
-    .bind(user_identifier)
-    .bind(details)
-    .bind(success)
-    .execute(db)
-    .await
-    {
+    let entry = new_entry(action, entity_type, entity_id, actor, details, success);
+
+    let result = async {
+        let mut tx = db.begin().await?;
+        append(&mut tx, entry, current_context()).await?;
+        tx.commit().await
+    }
+    .await;
+    if let Err(err) = result {
         tracing::warn!("Failed to write audit log '{}': {:?}", action, err);
     }
 }
+
+/// Like `log_action`, but writes through the caller's transaction so the entry commits or
+/// rolls back with the change it records. Errors are returned to the caller.
+pub async fn log_action_in(
+    conn: &mut PgConnection,
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<Uuid>,
+    actor: Option<Actor>,
+    details: Option<Value>,
+    success: bool,
+) -> Result<(), sqlx::Error> {
+    let entry = new_entry(action, entity_type, entity_id, actor, details, success);
+    append(conn, entry, current_context()).await
+}
+
+/// Like `log_action`, but leaves out the request id, client address and user agent, for
+/// entries that must not be traceable to whoever made the request. Written in its own
+/// transaction, so it shares no transaction id with the change it records.
+pub async fn log_unattributed(
+    db: &PgPool,
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<Uuid>,
+    details: Option<Value>,
+    success: bool,
+) {
+    let entry = new_entry(action, entity_type, entity_id, None, details, success);
+
+    let result = async {
+        let mut tx = db.begin().await?;
+        append(&mut tx, entry, AuditContext::default()).await?;
+        tx.commit().await
+    }
+    .await;
+    if let Err(err) = result {
+        tracing::warn!("Failed to write audit log '{}': {:?}", action, err);
+    }
+}
+
+fn new_entry(
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<Uuid>,
+    actor: Option<Actor>,
+    details: Option<Value>,
+    success: bool,
+) -> ChainEntry {
+    ChainEntry {
+        chain_seq: 0,
+        prev_hash: String::new(),
+        entry_hash: String::new(),
+        // Postgres keeps microseconds; truncate so the hash survives the round-trip
+        timestamp: Utc::now().trunc_subsecs(6),
+        action: action.to_string(),
+        entity_type: entity_type.to_string(),
+        entity_id,
+        user_identifier: actor.map(|Actor(identifier)| identifier),
+        details,
+        success,
+    }
+}
+
+/// The wentu whose chain an entry belongs to, if any.
+async fn chain_wentu_id(
+    conn: &mut PgConnection,
+    entity_type: &str,
+    entity_id: Option<Uuid>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    match (entity_type, entity_id) {
+        ("wentu", Some(wentu_id)) => Ok(Some(wentu_id)),
+        ("participant", Some(participant_id)) => {
+            sqlx::query_scalar::<_, Uuid>("SELECT wentu_id FROM participants WHERE id = $1")
+                .bind(participant_id)
+                .fetch_optional(conn)
+                .await
+        }
+        _ => Ok(None),
+    }
+}
+
+async fn append(
+    conn: &mut PgConnection,
+    mut entry: ChainEntry,
+    context: AuditContext,
+) -> Result<(), sqlx::Error> {
+    let wentu_id = chain_wentu_id(conn, &entry.entity_type, entry.entity_id).await?;
+    let chained = match wentu_id {
+        Some(wentu_id) => {
+            let (seq, prev_hash) = audit_chain::next_link(conn, wentu_id).await?;
+            entry.chain_seq = seq;
+            entry.prev_hash = prev_hash;
+            entry.entry_hash = entry.compute_hash(wentu_id);
+            true
+        }
+        None => false,
+    };
+
+    sqlx::query(
+        "INSERT INTO audit_logs (timestamp, action, entity_type, entity_id, user_identifier, details, success,
+                                 request_id, ip_address, user_agent, wentu_id, chain_seq, prev_hash, entry_hash)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+    )
+    .bind(entry.timestamp)
+    .bind(&entry.action)
+    .bind(&entry.entity_type)
+    .bind(entry.entity_id)
+    .bind(&entry.user_identifier)
+    .bind(&entry.details)
+    .bind(entry.success)
+    .bind(context.request_id)
+    .bind(context.ip_address)
+    .bind(context.user_agent)
+    .bind(wentu_id)
+    .bind(chained.then_some(entry.chain_seq))
+    .bind(chained.then_some(&entry.prev_hash))
+    .bind(chained.then_some(&entry.entry_hash))
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use std::path::Path;
+
+    /// Argument lists of every call to one of the `log_*` functions above in `source`.
+    fn log_action_calls(source: &str) -> Vec<&str> {
+        Regex::new(r"log_(action|action_in|unattributed)\(")
+            .unwrap()
+            .find_iter(source)
+            .map(|call| (call.start(), call.as_str()))
+            .map(|(start, call)| {
+                let args = start + call.len();
+                let mut depth = 1;
+                let end = source[args..]
+                    .char_indices()
+                    .find_map(|(i, c)| {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        (depth == 0).then_some(args + i)
+                    })
+                    .expect("unbalanced log_action call");
+                &source[args..end]
+            })
+            .collect()
+    }
+
+    fn rust_sources(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                rust_sources(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    /// Keys never reach the audit API.
+    #[test]
+    fn test_no_keys_passed_to_audit_log() {
+        let key = Regex::new(r"(?i)key").unwrap();
+
+        let mut files = Vec::new();
+        rust_sources(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut files,
+        );
+
+        let mut offenders = Vec::new();
+        for file in files.iter().filter(|file| !file.ends_with("audit.rs")) {
+            let source = std::fs::read_to_string(file).unwrap();
+            for args in log_action_calls(&source) {
+                if key.is_match(args) {
+                    offenders.push(format!("{}: log_action({})", file.display(), args.trim()));
+                }
+            }
+        }
+        assert!(files.len() > 1);
+        assert!(
+            offenders.is_empty(),
+            "keys passed to audit log:\n{}",
+            offenders.join("\n")
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgConnection};
+use uuid::Uuid;
+
+/// `prev_hash` of the first entry in every wentu's chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// The hashed part of an audit entry. Client address, user agent and request id are left
+/// out so they can be scrubbed later without breaking the chain.
+#[derive(Debug, Clone, FromRow)]
+pub struct ChainEntry {
+    pub chain_seq: i64,
+    pub prev_hash: String,
+    pub entry_hash: String,
+    pub timestamp: DateTime<Utc>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<Uuid>,
+    pub user_identifier: Option<String>,
+    pub details: Option<Value>,
+    pub success: bool,
+}
+
+/// Canonical form of an entry; field order and `Value`'s sorted keys make the JSON stable
+/// across a round-trip through JSONB.
+#[derive(Serialize)]
+struct Payload<'a> {
+    wentu_id: Uuid,
+    seq: i64,
+    timestamp: String,
+    action: &'a str,
+    entity_type: &'a str,
+    entity_id: Option<Uuid>,
+    user_identifier: Option<&'a str>,
+    details: Option<&'a Value>,
+    success: bool,
+}
+
+impl ChainEntry {
+    /// SHA-256 over the previous entry's hash and this entry's payload.
+    pub fn compute_hash(&self, wentu_id: Uuid) -> String {
+        let payload = Payload {
+            wentu_id,
+            seq: self.chain_seq,
+            timestamp: self.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+            action: &self.action,
+            entity_type: &self.entity_type,
+            entity_id: self.entity_id,
+            user_identifier: self.user_identifier.as_deref(),
+            details: self.details.as_ref(),
+            success: self.success,
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(self.prev_hash.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(serde_json::to_vec(&payload).expect("audit payload serializes"));
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// Lock the wentu's chain for the rest of the transaction and return the sequence number
+/// and `prev_hash` for the next entry.
+pub async fn next_link(
+    conn: &mut PgConnection,
+    wentu_id: Uuid,
+) -> Result<(i64, String), sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1::text, 0))")
+        .bind(wentu_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let head = sqlx::query_as::<_, (i64, String)>(
+        "SELECT chain_seq, entry_hash FROM audit_logs
+         WHERE wentu_id = $1 AND chain_seq IS NOT NULL
+         ORDER BY chain_seq DESC LIMIT 1",
+    )
+    .bind(wentu_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(match head {
+        Some((seq, hash)) => (seq + 1, hash),
+        None => (1, GENESIS_HASH.to_string()),
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ChainBreak {
+    pub seq: i64,
+    pub reason: &'static str,
+}
+
+/// Walk a wentu's chain in sequence order, returning the head hash (`None` when empty) or
+/// the first entry that is missing, out of order or altered.
+pub fn verify(wentu_id: Uuid, entries: &[ChainEntry]) -> Result<Option<String>, ChainBreak> {
+    let mut prev_hash = GENESIS_HASH;
+
+    for (expected_seq, entry) in (1..).zip(entries) {
+        if entry.chain_seq != expected_seq {
+            return Err(ChainBreak {
+                seq: expected_seq,
+                reason: "missing entry",
+            });
+        }
+        if entry.prev_hash != prev_hash {
+            return Err(ChainBreak {
+                seq: entry.chain_seq,
+                reason: "broken link",
+            });
+        }
+        if entry.compute_hash(wentu_id) != entry.entry_hash {
+            return Err(ChainBreak {
+                seq: entry.chain_seq,
+                reason: "entry modified",
+            });
+        }
+        prev_hash = &entry.entry_hash;
+    }
+
+    Ok(entries.last().map(|entry| entry.entry_hash.clone()))
+}
+
+/// Digest of a participant's ballot as `(date_option_id, preference_order)` pairs,
+/// independent of the order they were submitted or stored in. Keyed with the wentu's ballot
+/// salt: option ids are public and rankings few, so a plain hash would reveal the ballot.
+pub fn ballot_digest(salt: &str, rankings: &[(Uuid, i32)]) -> String {
+    let mut rankings = rankings.to_vec();
+    rankings.sort_by_key(|&(option, order)| (order, option));
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(salt.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(b"ballot_digest.");
+    for (option, order) in rankings {
+        mac.update(format!("{}:{}\n", order, option).as_bytes());
+    }
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chain(wentu_id: Uuid, actions: &[&str]) -> Vec<ChainEntry> {
+        let mut entries: Vec<ChainEntry> = Vec::new();
+        for (seq, action) in (1..).zip(actions) {
+            let mut entry = ChainEntry {
+                chain_seq: seq,
+                prev_hash: entries
+                    .last()
+                    .map_or(GENESIS_HASH.to_string(), |prev| prev.entry_hash.clone()),
+                entry_hash: String::new(),
+                timestamp: Utc::now(),
+                action: action.to_string(),
+                entity_type: "participant".to_string(),
+                entity_id: Some(Uuid::new_v4()),
+                user_identifier: None,
+                details: Some(json!({ "rankings": seq })),
+                success: true,
+            };
+            entry.entry_hash = entry.compute_hash(wentu_id);
+            entries.push(entry);
+        }
+        entries
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let wentu_id = Uuid::new_v4();
+        let entries = chain(
+            wentu_id,
+            &["CREATE_WENTU", "JOIN_WENTU", "UPDATE_PREFERENCES"],
+        );
+
+        assert_eq!(
+            verify(wentu_id, &entries),
+            Ok(Some(entries[2].entry_hash.clone()))
+        );
+        assert_eq!(verify(wentu_id, &[]), Ok(None));
+
+        let mut edited = entries.clone();
+        edited[1].details = Some(json!({ "rankings": 99 }));
+        assert_eq!(verify(wentu_id, &edited).unwrap_err().seq, 2);
+
+        let mut deleted = entries.clone();
+        deleted.remove(1);
+        assert_eq!(
+            verify(wentu_id, &deleted).unwrap_err().reason,
+            "missing entry"
+        );
+
+        // A valid chain for one wentu does not verify as another's
+        assert!(verify(Uuid::new_v4(), &entries).is_err());
+    }
+
+    #[test]
+    fn test_ballot_digest_ignores_submission_order() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert_eq!(
+            ballot_digest("salt", &[(a, 1), (b, 2)]),
+            ballot_digest("salt", &[(b, 2), (a, 1)])
+        );
+        assert_ne!(
+            ballot_digest("salt", &[(a, 1), (b, 2)]),
+            ballot_digest("salt", &[(a, 2), (b, 1)])
+        );
+    }
+
+    /// Digests are checked against ballots long after they were recorded, possibly by a
+    /// server restarted with a new `TOKEN_SECRET`; only the wentu's stored salt goes into them.
+    #[test]
+    fn test_ballot_digest_survives_signer_rebuild() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        // As recorded by `update_preferences`
+        let recorded = ballot_digest("stored-salt", &[(a, 1), (b, 2)]);
+
+        // As recomputed by `verify_audit_chain` after the restart
+        assert_eq!(ballot_digest("stored-salt", &[(b, 2), (a, 1)]), recorded);
+    }
+}
@@ -0,0 +1,307 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap, StatusCode},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::handlers::AppState;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const TOKEN_VERSION: &str = "v1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Creator,
+    Participant,
+}
+
+impl Role {
+    pub fn from_is_creator(is_creator: bool) -> Self {
+        if is_creator {
+            Role::Creator
+        } else {
+            Role::Participant
+        }
+    }
+}
+
+/// Claims carried inside a participant bearer token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenClaims {
+    pub wentu_id: Uuid,
+    pub participant_id: Uuid,
+    pub role: Role,
+    /// Expiry as a unix timestamp (seconds), mirrors `participants.token_expires_at`
+    pub exp: i64,
+    /// Must match `participants.token_generation`; bumping it revokes the token
+    #[serde(default)]
+    pub gen: i32,
+}
+
+impl TokenClaims {
+    pub fn new(
+        wentu_id: Uuid,
+        participant_id: Uuid,
+        role: Role,
+        expires_at: DateTime<Utc>,
+        generation: i32,
+    ) -> Self {
+        Self {
+            wentu_id,
+            participant_id,
+            role,
+            exp: expires_at.timestamp(),
+            gen: generation,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenError {
+    #[error("malformed token")]
+    Malformed,
+    #[error("invalid token signature")]
+    BadSignature,
+    #[error("token expired")]
+    Expired,
+}
+
+/// Issues and verifies HMAC-SHA256 signed tokens of the form `v1.<claims>.<signature>`.
+#[derive(Clone)]
+pub struct TokenSigner {
+    key: Arc<Vec<u8>>,
+}
+
+impl TokenSigner {
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            key: Arc::new(secret.to_vec()),
+        }
+    }
+
+    /// Signer with a random per-process key; tokens will not survive a restart.
+    pub fn ephemeral() -> Self {
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        Self::new(&secret)
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length")
+    }
+
+    pub fn issue(&self, claims: &TokenClaims) -> String {
+        let payload = serde_json::to_vec(claims).expect("token claims serialize");
+        let signed = format!("{}.{}", TOKEN_VERSION, URL_SAFE_NO_PAD.encode(payload));
+
+        let mut mac = self.mac();
+        mac.update(signed.as_bytes());
+        let signature = mac.finalize().into_bytes();
+
+        format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    /// Verify signature and expiry, returning the embedded claims.
+    pub fn verify(&self, token: &str) -> Result<TokenClaims, TokenError> {
+        let claims = self.verify_signature(token)?;
+        if claims.exp <= Utc::now().timestamp() {
+            return Err(TokenError::Expired);
+        }
+        Ok(claims)
+    }
+
+    /// Verify the signature only; callers must apply their own expiry policy.
+    pub fn verify_signature(&self, token: &str) -> Result<TokenClaims, TokenError> {
+        let (signed, signature) = token.rsplit_once('.').ok_or(TokenError::Malformed)?;
+        let (version, payload) = signed.split_once('.').ok_or(TokenError::Malformed)?;
+        if version != TOKEN_VERSION {
+            return Err(TokenError::Malformed);
+        }
+
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| TokenError::Malformed)?;
+        let mut mac = self.mac();
+        mac.update(signed.as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| TokenError::BadSignature)?;
+
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| TokenError::Malformed)?;
+        serde_json::from_slice(&payload).map_err(|_| TokenError::Malformed)
+    }
+}
+
+/// Reject tokens whose participant is gone or whose generation has been revoked.
+pub async fn check_not_revoked(db: &PgPool, claims: &TokenClaims) -> Result<(), StatusCode> {
+    let generation = sqlx::query_scalar::<_, i32>(
+        "SELECT token_generation FROM participants WHERE id = $1 AND wentu_id = $2",
+    )
+    .bind(claims.participant_id)
+    .bind(claims.wentu_id)
+    .fetch_optional(db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if generation != claims.gen {
+        tracing::warn!(
+            "Rejected revoked token for participant {}",
+            claims.participant_id
+        );
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(())
+}
+
+/// Authenticated participant extracted from an `Authorization: Bearer` header.
+///
+/// Handlers still have to check that `wentu_id` matches the wentu named in the path.
+#[derive(Debug, Clone)]
+pub struct AuthParticipant {
+    pub wentu_id: Uuid,
+    pub participant_id: Uuid,
+    pub role: Role,
+}
+
+impl AuthParticipant {
+    pub fn is_creator(&self) -> bool {
+        self.role == Role::Creator
+    }
+
+    /// Verify the request's bearer token. Unlike the extractor, leaves the request span
+    /// untagged, for handlers that must not tie the request to its caller.
+    pub async fn from_headers(state: &AppState, headers: &HeaderMap) -> Result<Self, StatusCode> {
+        let token = bearer_token(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+
+        let claims = state.tokens.verify(token).map_err(|e| {
+            tracing::warn!("Rejected bearer token: {}", e);
+            StatusCode::UNAUTHORIZED
+        })?;
+
+        check_not_revoked(&state.db, &claims).await?;
+
+        Ok(AuthParticipant {
+            wentu_id: claims.wentu_id,
+            participant_id: claims.participant_id,
+            role: claims.role,
+        })
+    }
+
+    /// Tag the request span (and so its logs) with the participant.
+    pub fn record_span(&self) {
+        tracing::Span::current().record(
+            "participant_id",
+            tracing::field::display(self.participant_id),
+        );
+    }
+
+    /// Resolve the wentu named in the path and make sure the token was issued for it.
+    pub async fn authorize_slug(&self, db: &PgPool, slug: &str) -> Result<(), StatusCode> {
+        let wentu_id = sqlx::query_scalar::<_, Uuid>("SELECT id FROM wentus WHERE slug = $1")
+            .bind(slug)
+            .fetch_optional(db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+
+        if wentu_id != self.wentu_id {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        Ok(())
+    }
+}
+
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthParticipant {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let auth = AuthParticipant::from_headers(state, &parts.headers).await?;
+        auth.record_span();
+        Ok(auth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(expires_at: DateTime<Utc>) -> TokenClaims {
+        TokenClaims::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Role::Participant,
+            expires_at,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let signer = TokenSigner::new(b"test-secret");
+        let issued = claims(Utc::now() + chrono::Duration::hours(1));
+
+        let token = signer.issue(&issued);
+        let verified = signer.verify(&token).unwrap();
+
+        assert_eq!(verified.wentu_id, issued.wentu_id);
+        assert_eq!(verified.participant_id, issued.participant_id);
+        assert_eq!(verified.role, Role::Participant);
+    }
+
+    #[test]
+    fn test_rejects_tampered_claims() {
+        let signer = TokenSigner::new(b"test-secret");
+        let token = signer.issue(&claims(Utc::now() + chrono::Duration::hours(1)));
+
+        let forged = TokenClaims::new(Uuid::new_v4(), Uuid::new_v4(), Role::Creator, Utc::now(), 0);
+        let forged_payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged).unwrap());
+        let parts: Vec<&str> = token.split('.').collect();
+        let tampered = format!("{}.{}.{}", parts[0], forged_payload, parts[2]);
+
+        assert!(matches!(
+            signer.verify(&tampered),
+            Err(TokenError::BadSignature)
+        ));
+        assert!(matches!(
+            TokenSigner::new(b"other-secret").verify(&token),
+            Err(TokenError::BadSignature)
+        ));
+    }
+
+    #[test]
+    fn test_rejects_expired() {
+        let signer = TokenSigner::new(b"test-secret");
+        let token = signer.issue(&claims(Utc::now() - chrono::Duration::seconds(1)));
+
+        assert!(matches!(signer.verify(&token), Err(TokenError::Expired)));
+        assert!(signer.verify_signature(&token).is_ok());
+    }
+}
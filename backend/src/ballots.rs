@@ -0,0 +1,310 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::fmt::Write;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A date option as a candidate in the count.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Candidate {
+    pub id: Uuid,
+    pub label: String,
+}
+
+/// A ballot with the voter replaced by an opaque id.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnonymousBallot {
+    pub id: String,
+    /// Date option ids, most preferred first
+    pub ranking: Vec<Uuid>,
+    /// What the voter's receipt shows; recomputable from `id` and `ranking`
+    pub hash: String,
+}
+
+/// The wentu's date options in display order.
+pub async fn load_candidates(db: &PgPool, wentu_id: Uuid) -> Result<Vec<Candidate>, sqlx::Error> {
+    sqlx::query_as::<_, Candidate>(
+        "SELECT id, label FROM date_ranges WHERE wentu_id = $1 ORDER BY sort_order",
+    )
+    .bind(wentu_id)
+    .fetch_all(db)
+    .await
+}
+
+/// Every voter's ranked date options, most preferred first, keyed by participant and the
+/// version of their ballot.
+pub async fn load_rankings(
+    db: &PgPool,
+    wentu_id: Uuid,
+) -> Result<HashMap<(Uuid, i32), Vec<Uuid>>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, i32, Uuid)>(
+        "SELECT r.participant_id, p.ballot_version, r.date_option_id
+         FROM rankings r
+         JOIN participants p ON p.id = r.participant_id
+         WHERE r.date_option_id IN (SELECT id FROM date_ranges WHERE wentu_id = $1)
+         ORDER BY r.participant_id, r.preference_order",
+    )
+    .bind(wentu_id)
+    .fetch_all(db)
+    .await?;
+
+    let mut rankings: HashMap<(Uuid, i32), Vec<Uuid>> = HashMap::new();
+    for (participant_id, version, date_option_id) in rows {
+        rankings
+            .entry((participant_id, version))
+            .or_default()
+            .push(date_option_id);
+    }
+    Ok(rankings)
+}
+
+/// Every voter's ranked date options as they stood at `as_of`, from the ballot history.
+pub async fn load_rankings_as_of(
+    db: &PgPool,
+    wentu_id: Uuid,
+    as_of: DateTime<Utc>,
+) -> Result<HashMap<Uuid, Vec<Uuid>>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, Vec<Uuid>)>(
+        "SELECT DISTINCT ON (participant_id) participant_id, ranking
+         FROM ballot_history
+         WHERE wentu_id = $1 AND recorded_at <= $2
+         ORDER BY participant_id, version DESC",
+    )
+    .bind(wentu_id)
+    .bind(as_of)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Ballots of an anonymous wentu, most preferred first, keyed by their random ballot id.
+pub async fn load_anonymous_rankings(
+    db: &PgPool,
+    wentu_id: Uuid,
+) -> Result<HashMap<Uuid, Vec<Uuid>>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, Uuid)>(
+        "SELECT ballot_id, date_option_id
+         FROM anonymous_rankings
+         WHERE wentu_id = $1
+         ORDER BY ballot_id, preference_order",
+    )
+    .bind(wentu_id)
+    .fetch_all(db)
+    .await?;
+
+    let mut rankings: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (ballot_id, date_option_id) in rows {
+        rankings.entry(ballot_id).or_default().push(date_option_id);
+    }
+    Ok(rankings)
+}
+
+/// Every ballot cast in the wentu as published, whether stored per participant or
+/// anonymously, ordered by id.
+pub async fn load_ballots(
+    db: &PgPool,
+    wentu_id: Uuid,
+    salt: &str,
+) -> Result<Vec<AnonymousBallot>, sqlx::Error> {
+    let mut ballots = anonymize(salt, load_rankings(db, wentu_id).await?);
+    // Anonymous ballot ids are random already
+    ballots.extend(
+        load_anonymous_rankings(db, wentu_id)
+            .await?
+            .into_iter()
+            .map(|(ballot_id, ranking)| published(ballot_id.to_string(), ranking)),
+    );
+    ballots.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(ballots)
+}
+
+/// Opaque id for one version of a participant's ballot, keyed with the wentu's ballot salt
+/// so it cannot be linked back to the participant without it. Each version gets a new id,
+/// so an edited ballot cannot be followed from one export to the next.
+pub fn ballot_id(salt: &str, participant_id: Uuid, version: i32) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(salt.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("ballot.{}.{}", participant_id, version).as_bytes());
+    hex::encode(&mac.finalize().into_bytes()[..16])
+}
+
+/// Hash of a ballot as published: SHA-256 over the ballot id and the ranked option ids,
+/// one per line.
+pub fn receipt_hash(ballot_id: &str, ranking: &[Uuid]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(ballot_id.as_bytes());
+    for option in ranking {
+        hasher.update(format!("\n{}", option).as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+fn published(id: String, ranking: Vec<Uuid>) -> AnonymousBallot {
+    AnonymousBallot {
+        hash: receipt_hash(&id, &ranking),
+        id,
+        ranking,
+    }
+}
+
+/// Strip voter identity from ballots. Sorting by the keyed ids shuffles them, so the order
+/// reveals nothing about who voted when.
+pub fn anonymize(salt: &str, rankings: HashMap<(Uuid, i32), Vec<Uuid>>) -> Vec<AnonymousBallot> {
+    let mut ballots: Vec<AnonymousBallot> = rankings
+        .into_iter()
+        .map(|((participant_id, version), ranking)| {
+            published(ballot_id(salt, participant_id, version), ranking)
+        })
+        .collect();
+    ballots.sort_by(|a, b| a.id.cmp(&b.id));
+    ballots
+}
+
+/// BLT has no escaping; keep names on one line and inside their quotes.
+fn blt_string(value: &str) -> String {
+    let cleaned: String = value
+        .chars()
+        .map(|c| match c {
+            '"' => '\'',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    format!("\"{}\"", cleaned)
+}
+
+/// Ballots in the BLT format read by OpenSTV, Droop and most STV counters: a header with
+/// the candidate and seat counts, one weighted ballot per line using 1-based candidate
+/// numbers, then the candidate names and title.
+pub fn to_blt(title: &str, candidates: &[Candidate], ballots: &[AnonymousBallot]) -> String {
+    let numbers: HashMap<Uuid, usize> = candidates
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| (candidate.id, index + 1))
+        .collect();
+
+    // One winning date
+    let mut blt = format!("{} 1\n", candidates.len());
+    for ballot in ballots {
+        blt.push('1');
+        for option in &ballot.ranking {
+            if let Some(number) = numbers.get(option) {
+                let _ = write!(blt, " {}", number);
+            }
+        }
+        blt.push_str(" 0\n");
+    }
+    blt.push_str("0\n");
+    for candidate in candidates {
+        blt.push_str(&blt_string(&candidate.label));
+        blt.push('\n');
+    }
+    blt.push_str(&blt_string(title));
+    blt.push('\n');
+    blt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anonymize_hides_and_shuffles_voters() {
+        let salt = "test-salt";
+        let option = Uuid::new_v4();
+        let voters: Vec<Uuid> = (0..5).map(|_| Uuid::new_v4()).collect();
+        let rankings: HashMap<(Uuid, i32), Vec<Uuid>> = voters
+            .iter()
+            .map(|&voter| ((voter, 1), vec![option]))
+            .collect();
+
+        let ballots = anonymize(salt, rankings.clone());
+        assert_eq!(ballots.len(), 5);
+        assert!(ballots.windows(2).all(|pair| pair[0].id < pair[1].id));
+        assert_eq!(
+            ballots[0].hash,
+            receipt_hash(&ballots[0].id, &ballots[0].ranking)
+        );
+
+        // Stable across exports, but not linkable across wentus
+        let again = anonymize(salt, rankings.clone());
+        assert_eq!(ballots[0].id, again[0].id);
+        let elsewhere = anonymize("other-salt", rankings);
+        assert!(elsewhere
+            .iter()
+            .all(|ballot| ballots.iter().all(|other| other.id != ballot.id)));
+    }
+
+    #[test]
+    fn test_ballot_id_changes_with_each_version() {
+        let participant = Uuid::new_v4();
+
+        assert_eq!(
+            ballot_id("salt", participant, 1),
+            ballot_id("salt", participant, 1)
+        );
+        assert_ne!(
+            ballot_id("salt", participant, 1),
+            ballot_id("salt", participant, 2)
+        );
+    }
+
+    /// A receipt is checked against the export long after it was issued, possibly by a
+    /// server restarted with a new `TOKEN_SECRET`; only the wentu's stored salt goes into it.
+    #[test]
+    fn test_receipt_survives_signer_rebuild() {
+        let salt = "stored-salt";
+        let (participant, option) = (Uuid::new_v4(), Uuid::new_v4());
+
+        // As issued by `update_preferences`
+        let issued_id = ballot_id(salt, participant, 3);
+        let receipt = receipt_hash(&issued_id, &[option]);
+
+        // As published after the restart
+        let published = anonymize(salt, HashMap::from([((participant, 3), vec![option])]));
+        assert_eq!(published[0].id, issued_id);
+        assert_eq!(published[0].hash, receipt);
+    }
+
+    #[test]
+    fn test_to_blt() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let candidates = vec![
+            Candidate {
+                id: a,
+                label: "Tue 12th".to_string(),
+            },
+            Candidate {
+                id: b,
+                label: "Wed \"13th\"".to_string(),
+            },
+            Candidate {
+                id: c,
+                label: "Thu 14th".to_string(),
+            },
+        ];
+        let ballots = vec![
+            AnonymousBallot {
+                id: "x".to_string(),
+                ranking: vec![c, a],
+                hash: String::new(),
+            },
+            AnonymousBallot {
+                id: "y".to_string(),
+                ranking: vec![b],
+                hash: String::new(),
+            },
+        ];
+
+        assert_eq!(
+            to_blt("Team\nOffsite", &candidates, &ballots),
+            "3 1\n1 3 1 0\n1 2 0\n0\n\"Tue 12th\"\n\"Wed '13th'\"\n\"Thu 14th\"\n\"Team Offsite\"\n"
+        );
+    }
+}
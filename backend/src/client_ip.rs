@@ -0,0 +1,148 @@
+use axum::extract::ConnectInfo;
+use axum::http::{HeaderMap, HeaderName, Request};
+use ipnet::IpNet;
+use serde::Deserialize;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tower_governor::{key_extractor::KeyExtractor, GovernorError};
+
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+const X_REAL_IP: HeaderName = HeaderName::from_static("x-real-ip");
+
+/// Reverse proxies whose forwarding headers are believed, from `TRUSTED_PROXIES`
+/// (comma-separated IPs or CIDRs, e.g. `10.0.0.0/8, 127.0.0.1`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TrustedProxies(Vec<IpNet>);
+
+impl TryFrom<String> for TrustedProxies {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                entry
+                    .parse::<IpNet>()
+                    .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| format!("invalid trusted proxy '{}'", entry))
+            })
+            .collect::<Result<_, _>>()
+            .map(TrustedProxies)
+    }
+}
+
+impl TrustedProxies {
+    fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.0.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Resolve the client address. Forwarding headers are only honoured when the peer is a
+    /// trusted proxy; `X-Forwarded-For` is walked right to left past any further trusted
+    /// proxies, so a client cannot spoof its address by prepending entries. Falls back to
+    /// `X-Real-IP`, then the peer itself.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let peer = peer.to_canonical();
+        if !self.contains(peer) {
+            return peer;
+        }
+
+        let hops: Vec<&str> = headers
+            .get_all(X_FORWARDED_FOR)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+        for hop in hops.iter().rev() {
+            match hop.parse::<IpAddr>() {
+                Ok(ip) if self.contains(ip) => continue,
+                Ok(ip) => return ip.to_canonical(),
+                // Anything left of a malformed hop cannot be attributed reliably
+                Err(_) => break,
+            }
+        }
+
+        headers
+            .get(X_REAL_IP)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<IpAddr>().ok())
+            .map(|ip| ip.to_canonical())
+            .unwrap_or(peer)
+    }
+}
+
+/// Rate limit key extractor using the same client address resolution as the audit log.
+#[derive(Debug, Clone)]
+pub struct ClientIpKeyExtractor {
+    pub proxies: Arc<TrustedProxies>,
+}
+
+impl KeyExtractor for ClientIpKeyExtractor {
+    type Key = IpAddr;
+
+    fn extract<T>(&self, req: &Request<T>) -> Result<Self::Key, GovernorError> {
+        req.extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| self.proxies.client_ip(peer.ip(), req.headers()))
+            .ok_or(GovernorError::UnableToExtractKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn proxies(list: &str) -> TrustedProxies {
+        TrustedProxies::try_from(list.to_string()).unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(X_FORWARDED_FOR, HeaderValue::from_static("1.2.3.4"));
+
+        assert_eq!(
+            TrustedProxies::default().client_ip(ip("203.0.113.9"), &headers),
+            ip("203.0.113.9")
+        );
+    }
+
+    #[test]
+    fn test_trusted_proxy_chain() {
+        let trusted = proxies("10.0.0.0/8, 127.0.0.1");
+        let mut headers = HeaderMap::new();
+        // Client spoofed 6.6.6.6; the real client was seen by our proxies as 198.51.100.7
+        headers.insert(
+            X_FORWARDED_FOR,
+            HeaderValue::from_static("6.6.6.6, 198.51.100.7, 10.1.2.3"),
+        );
+
+        assert_eq!(
+            trusted.client_ip(ip("127.0.0.1"), &headers),
+            ip("198.51.100.7")
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(X_REAL_IP, HeaderValue::from_static("198.51.100.8"));
+        assert_eq!(
+            trusted.client_ip(ip("::ffff:10.0.0.5"), &headers),
+            ip("198.51.100.8")
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_entries() {
+        assert!(TrustedProxies::try_from("10.0.0.0/33".to_string()).is_err());
+        assert!(TrustedProxies::try_from("proxy.internal".to_string()).is_err());
+        assert_eq!(proxies(" "), TrustedProxies::default());
+    }
+}
@@ -1,8 +1,23 @@
 pub mod pool;
 
 pub use pool::create_pool;
+use sqlx::migrate::Migrator;
 use sqlx::PgPool;
 
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 pub async fn run_migrations(pool: &PgPool) -> Result<(), sqlx::migrate::MigrateError> {
-    sqlx::migrate!("./migrations").run(pool).await
+    MIGRATOR.run(pool).await
+}
+
+/// Newest migration embedded in this build.
+pub fn latest_migration_version() -> Option<i64> {
+    MIGRATOR.iter().map(|migration| migration.version).max()
+}
+
+/// Newest migration successfully applied to the database.
+pub async fn applied_migration_version(pool: &PgPool) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+        .fetch_one(pool)
+        .await
 }
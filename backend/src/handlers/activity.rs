@@ -0,0 +1,377 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::AppState;
+use crate::access::{self, ResultsPolicy};
+use crate::audit_chain::{self, ChainEntry};
+use crate::auth::AuthParticipant;
+use crate::models::{
+    ActivityEntry, ActivityPage, ActivityParticipant, ActivityQuery, AuditVerification,
+    BallotVerification,
+};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Detail fields a creator may see. Everything else (slugs, invite ids, anything added to
+/// the audit log later) is dropped unless listed here.
+const VISIBLE_DETAILS: &[&str] = &[
+    "name",
+    "title",
+    "reason",
+    "rankings",
+    "invites",
+    "invite_only",
+    "anonymous",
+    "passphrase_protected",
+    "results_visibility",
+];
+
+fn redact_details(details: Option<Value>) -> Value {
+    let visible: Map<String, Value> = match details {
+        Some(Value::Object(fields)) => fields
+            .into_iter()
+            .filter(|(key, _)| VISIBLE_DETAILS.contains(&key.as_str()))
+            .collect(),
+        _ => Map::new(),
+    };
+    Value::Object(visible)
+}
+
+fn participant(id: Option<Uuid>, name: Option<String>) -> Option<ActivityParticipant> {
+    Some(ActivityParticipant {
+        id: id?,
+        name: name?,
+    })
+}
+
+/// Activity feed for a wentu, newest first (creator only). Covers wentu-level entries
+/// (creation, invites, close and passphrase attempts) and entries for its participants
+/// (joins, ballot updates, token refreshes and revocations). Client addresses and request ids
+/// are never returned.
+pub async fn get_activity(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Query(query): Query<ActivityQuery>,
+    auth: AuthParticipant,
+) -> Result<Json<ActivityPage>, StatusCode> {
+    query.validate().map_err(|e| {
+        tracing::warn!(
+            "get_activity validation failed: {:?}",
+            super::invalid_fields(&e)
+        );
+        StatusCode::BAD_REQUEST
+    })?;
+
+    if !auth.is_creator() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    auth.authorize_slug(&state.db, &slug).await?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+
+    // One extra row tells us whether there is another page
+    let rows = sqlx::query(
+        "SELECT a.id, a.timestamp, a.action, a.success, a.details,
+                actor.id AS actor_id, actor.name AS actor_name,
+                subject.id AS subject_id, subject.name AS subject_name
+         FROM audit_logs a
+         LEFT JOIN participants actor
+           ON actor.wentu_id = $1 AND a.user_identifier = 'participant:' || actor.id::text
+         LEFT JOIN participants subject
+           ON subject.wentu_id = $1 AND a.entity_type = 'participant' AND a.entity_id = subject.id
+         WHERE a.wentu_id = $1
+           AND ($2::text IS NULL OR a.action = $2)
+           AND ($3::uuid IS NULL OR actor.id = $3 OR subject.id = $3)
+           AND ($4::boolean IS NULL OR a.success = $4)
+           AND ($5::uuid IS NULL
+                OR (a.timestamp, a.id) < (SELECT timestamp, id FROM audit_logs WHERE id = $5))
+         ORDER BY a.timestamp DESC, a.id DESC
+         LIMIT $6",
+    )
+    .bind(auth.wentu_id)
+    .bind(query.action.as_deref())
+    .bind(query.participant_id)
+    .bind(query.success)
+    .bind(query.before)
+    .bind(limit + 1)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to fetch activity for {}: {:?}", slug, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut entries: Vec<ActivityEntry> = rows
+        .into_iter()
+        .map(|row| ActivityEntry {
+            id: row.get("id"),
+            timestamp: row.get("timestamp"),
+            action: row.get("action"),
+            success: row.get("success"),
+            actor: participant(row.get("actor_id"), row.get("actor_name")),
+            subject: participant(row.get("subject_id"), row.get("subject_name")),
+            details: redact_details(row.get("details")),
+        })
+        .collect();
+
+    let next_cursor = if entries.len() as i64 > limit {
+        entries.truncate(limit as usize);
+        entries.last().map(|entry| entry.id)
+    } else {
+        None
+    };
+
+    Ok(Json(ActivityPage {
+        entries,
+        next_cursor,
+    }))
+}
+
+/// Compare each participant's stored ballot with the digest from their latest recorded
+/// preference update in a verified chain. `legacy` holds participants whose ballot was
+/// stored before chaining existed.
+fn verify_ballots(
+    salt: &str,
+    entries: &[ChainEntry],
+    stored: &HashMap<Uuid, Vec<(Uuid, i32)>>,
+    legacy: &HashSet<Uuid>,
+) -> BallotVerification {
+    let mut recorded: HashMap<Uuid, &str> = HashMap::new();
+    for entry in entries {
+        if entry.action != "UPDATE_PREFERENCES" || !entry.success {
+            continue;
+        }
+        let digest = entry
+            .details
+            .as_ref()
+            .and_then(|details| details.get("ballot_digest"))
+            .and_then(Value::as_str);
+        if let (Some(participant_id), Some(digest)) = (entry.entity_id, digest) {
+            recorded.insert(participant_id, digest);
+        }
+    }
+
+    let participants: HashSet<Uuid> = recorded.keys().chain(stored.keys()).copied().collect();
+    let mut result = BallotVerification::default();
+    for participant_id in participants {
+        match (recorded.get(&participant_id), stored.get(&participant_id)) {
+            (Some(digest), ballot) => {
+                result.checked += 1;
+                let current = audit_chain::ballot_digest(salt, ballot.map_or(&[], Vec::as_slice));
+                if current != *digest {
+                    result.mismatched += 1;
+                }
+            }
+            (None, Some(_)) if legacy.contains(&participant_id) => result.legacy += 1,
+            (None, Some(_)) => result.unrecorded += 1,
+            (None, None) => {}
+        }
+    }
+    result
+}
+
+/// Walk the wentu's audit hash chain and check stored ballots against the digests recorded
+/// in it. Readable by anyone who can see the wentu's results, since ballot counts are revealed.
+pub async fn verify_audit_chain(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<AuditVerification>, StatusCode> {
+    let wentu_row = sqlx::query(
+        "SELECT id, access_passphrase_hash, status::text, expires_at, pref_deadline,
+                results_visibility, ballot_salt, anonymous
+         FROM wentus WHERE slug = $1",
+    )
+    .bind(&slug)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to fetch wentu: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let wentu_id: Uuid = wentu_row.get(0);
+    let access_passphrase_hash: Option<String> = wentu_row.get(1);
+    let status: String = wentu_row.get(2);
+    let expires_at: DateTime<Utc> = wentu_row.get(3);
+    let ballot_salt: String = wentu_row.get(6);
+    let anonymous: bool = wentu_row.get(7);
+
+    access::require_access(
+        &state,
+        wentu_id,
+        &slug,
+        access_passphrase_hash.as_deref(),
+        &headers,
+    )
+    .await?;
+
+    let policy = ResultsPolicy {
+        visibility: wentu_row.get(5),
+        closed: status != "open" || Utc::now() > expires_at,
+        pref_deadline: wentu_row.get(4),
+    };
+    access::require_results_visible(&state, wentu_id, policy, &headers).await?;
+
+    let entries = sqlx::query_as::<_, ChainEntry>(
+        "SELECT chain_seq, prev_hash, entry_hash, timestamp, action, entity_type, entity_id,
+                user_identifier, details, success
+         FROM audit_logs
+         WHERE wentu_id = $1 AND chain_seq IS NOT NULL
+         ORDER BY chain_seq",
+    )
+    .bind(wentu_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to fetch audit chain for {}: {:?}", slug, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let stored_rows = sqlx::query_as::<_, (Uuid, Uuid, i32, bool)>(
+        "SELECT r.participant_id, r.date_option_id, r.preference_order, p.ballot_predates_chain
+         FROM rankings r
+         JOIN participants p ON p.id = r.participant_id
+         WHERE p.wentu_id = $1",
+    )
+    .bind(wentu_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut stored: HashMap<Uuid, Vec<(Uuid, i32)>> = HashMap::new();
+    let mut legacy = HashSet::new();
+    for (participant_id, option_id, order, predates_chain) in stored_rows {
+        if predates_chain {
+            legacy.insert(participant_id);
+        }
+        stored
+            .entry(participant_id)
+            .or_default()
+            .push((option_id, order));
+    }
+
+    let verification = match audit_chain::verify(wentu_id, &entries) {
+        // Anonymous ballots are stored unlinked and never digested, so a clean chain says
+        // nothing about them
+        Ok(head_hash) if anonymous => AuditVerification {
+            valid: false,
+            entries: entries.len(),
+            head_hash,
+            broken_at: None,
+            reason: Some("anonymous ballots cannot be verified"),
+            ballots_verifiable: false,
+            ballots: BallotVerification::default(),
+        },
+        Ok(head_hash) => {
+            let ballots = verify_ballots(&ballot_salt, &entries, &stored, &legacy);
+            AuditVerification {
+                valid: ballots.mismatched == 0 && ballots.unrecorded == 0,
+                entries: entries.len(),
+                head_hash,
+                broken_at: None,
+                reason: None,
+                ballots_verifiable: true,
+                ballots,
+            }
+        }
+        Err(chain_break) => {
+            tracing::warn!(
+                "Audit chain for {} broken at {}: {}",
+                slug,
+                chain_break.seq,
+                chain_break.reason
+            );
+            AuditVerification {
+                valid: false,
+                entries: entries.len(),
+                head_hash: None,
+                broken_at: Some(chain_break.seq),
+                reason: Some(chain_break.reason),
+                ballots_verifiable: !anonymous,
+                // Digests from a broken chain prove nothing
+                ballots: BallotVerification::default(),
+            }
+        }
+    };
+
+    Ok(Json(verification))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_details_keeps_only_visible_fields() {
+        let details = json!({
+            "slug": "abc123",
+            "name": "Bob",
+            "invite_id": "6d0f4a4e-3c4b-4d8e-9f61-2a7f3c1b9e10",
+            "creator_key": "should-never-be-here",
+            "rankings": 3,
+        });
+
+        assert_eq!(
+            redact_details(Some(details)),
+            json!({ "name": "Bob", "rankings": 3 })
+        );
+        assert_eq!(redact_details(None), json!({}));
+        assert_eq!(redact_details(Some(json!("raw"))), json!({}));
+    }
+
+    #[test]
+    fn test_verify_ballots() {
+        let (alice, bob, carol, dave) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let option = Uuid::new_v4();
+        let vote = |participant_id: Uuid, ballot: &[(Uuid, i32)]| ChainEntry {
+            chain_seq: 1,
+            prev_hash: String::new(),
+            entry_hash: String::new(),
+            timestamp: chrono::Utc::now(),
+            action: "UPDATE_PREFERENCES".to_string(),
+            entity_type: "participant".to_string(),
+            entity_id: Some(participant_id),
+            user_identifier: None,
+            details: Some(json!({ "ballot_digest": audit_chain::ballot_digest("salt", ballot) })),
+            success: true,
+        };
+        let entries = [vote(alice, &[(option, 1)]), vote(bob, &[(option, 1)])];
+
+        // Bob's ballot was edited in the database; Carol's was never recorded; Dave's was
+        // cast before chaining existed
+        let stored = HashMap::from([
+            (alice, vec![(option, 1)]),
+            (bob, vec![(option, 2)]),
+            (carol, vec![(option, 1)]),
+            (dave, vec![(option, 1)]),
+        ]);
+
+        let result = verify_ballots("salt", &entries, &stored, &HashSet::from([dave]));
+        assert_eq!(
+            (
+                result.checked,
+                result.mismatched,
+                result.unrecorded,
+                result.legacy
+            ),
+            (2, 1, 1, 1)
+        );
+    }
+}
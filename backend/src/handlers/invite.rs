@@ -0,0 +1,176 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::RngCore;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use std::collections::HashSet;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::AppState;
+use crate::audit;
+use crate::auth::AuthParticipant;
+use crate::models::{CreateInvitesRequest, InviteLink};
+use crate::sanitize;
+
+/// Hash of an invite token as stored in `invites.token_hash`
+pub fn hash_invite_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn new_invite_token() -> String {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Sanitize a list of invitee names, dropping duplicates and lookalikes
+pub fn normalize_invitee_names(names: &[String]) -> Result<Vec<String>, StatusCode> {
+    let mut seen = HashSet::new();
+    let mut normalized = Vec::new();
+
+    for name in names {
+        let name = sanitize::sanitize_name(name).map_err(|e| {
+            tracing::warn!("Invalid invitee name: {}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+        if seen.insert(sanitize::skeleton(&name)) {
+            normalized.push(name);
+        }
+    }
+
+    Ok(normalized)
+}
+
+/// Store invites for already-normalized names.
+///
+/// Names with an unused invite get a fresh token (the old link stops working). Names whose
+/// invite was already redeemed get a fresh one too, for an invitee who lost their token:
+/// redeeming it resumes the same participant, and their existing tokens stop working.
+pub async fn insert_invites(
+    db: &PgPool,
+    wentu_id: Uuid,
+    slug: &str,
+    names: &[String],
+) -> Result<Vec<InviteLink>, StatusCode> {
+    let mut links = Vec::with_capacity(names.len());
+
+    for name in names {
+        let invite_token = new_invite_token();
+        let token_hash = hash_invite_token(&invite_token);
+
+        let existing = sqlx::query(
+            "SELECT id, participant_id FROM invites
+             WHERE wentu_id = $1 AND LOWER(name) = LOWER($2)",
+        )
+        .bind(wentu_id)
+        .bind(name)
+        .fetch_optional(db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let invite_id = match existing {
+            Some(row) => {
+                let invite_id: Uuid = row.get("id");
+                sqlx::query("UPDATE invites SET token_hash = $1, used_at = NULL WHERE id = $2")
+                    .bind(&token_hash)
+                    .bind(invite_id)
+                    .execute(db)
+                    .await
+                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+                // Whoever holds the invitee's old tokens loses them with the reissue
+                if let Some(participant_id) = row.get::<Option<Uuid>, _>("participant_id") {
+                    sqlx::query(
+                        "UPDATE participants SET token_generation = token_generation + 1
+                         WHERE id = $1",
+                    )
+                    .bind(participant_id)
+                    .execute(db)
+                    .await
+                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                }
+                invite_id
+            }
+            None => {
+                let invite_id = Uuid::new_v4();
+                sqlx::query(
+                    "INSERT INTO invites (id, wentu_id, name, token_hash) VALUES ($1, $2, $3, $4)",
+                )
+                .bind(invite_id)
+                .bind(wentu_id)
+                .bind(name)
+                .bind(&token_hash)
+                .execute(db)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to insert invite: {:?}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+                invite_id
+            }
+        };
+
+        links.push(InviteLink {
+            id: invite_id,
+            name: name.clone(),
+            link: format!("/wentu/{}?invite={}", slug, invite_token),
+            invite_token,
+        });
+    }
+
+    Ok(links)
+}
+
+/// Invite more people to an invite-only wentu (creator only)
+pub async fn create_invites(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    auth: AuthParticipant,
+    Json(req): Json<CreateInvitesRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), StatusCode> {
+    req.validate().map_err(|e| {
+        tracing::warn!(
+            "Validation failed for create_invites: {:?}",
+            super::invalid_fields(&e)
+        );
+        StatusCode::BAD_REQUEST
+    })?;
+
+    if !auth.is_creator() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    auth.authorize_slug(&state.db, &slug).await?;
+
+    let invite_only = sqlx::query_scalar::<_, bool>("SELECT invite_only FROM wentus WHERE id = $1")
+        .bind(auth.wentu_id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if !invite_only {
+        tracing::warn!("create_invites on open wentu {}", slug);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let names = normalize_invitee_names(&req.names)?;
+    let invites = insert_invites(&state.db, auth.wentu_id, &slug, &names).await?;
+
+    audit::log_action(
+        &state.db,
+        "CREATE_INVITES",
+        "wentu",
+        Some(auth.wentu_id),
+        Some(audit::Actor::participant(auth.participant_id)),
+        Some(json!({ "slug": slug, "invites": invites.len() })),
+        true,
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(json!({ "invites": invites }))))
+}
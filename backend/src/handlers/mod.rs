@@ -1,7 +1,19 @@
+pub mod activity;
+pub mod invite;
 pub mod participant;
 pub mod stv_handler;
+pub mod token;
 pub mod wentu;
 
-pub use participant::*;
 pub use stv_handler::get_stv_results;
 pub use wentu::*;
+
+use validator::ValidationErrors;
+
+/// Names of the fields that failed validation. Logged instead of the errors themselves,
+/// whose `Debug` output includes the submitted values (keys, passphrases).
+pub(crate) fn invalid_fields(errors: &ValidationErrors) -> Vec<&'static str> {
+    let mut fields: Vec<_> = errors.errors().keys().copied().collect();
+    fields.sort_unstable();
+    fields
+}
@@ -1,63 +1,124 @@
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     Json,
 };
 use chrono::{DateTime, Utc};
 use serde_json::json;
-use sqlx::Row;
+use sqlx::{FromRow, PgConnection, Row};
 use std::collections::HashSet;
 use uuid::Uuid;
 use validator::Validate;
 
-use super::AppState;
+use super::{invite, AppState};
+use crate::access;
 use crate::audit;
-use crate::models::wentu::SAFE_NAME_REGEX;
-use crate::models::{JoinWentuRequest, JoinWentuResponse, UpdatePreferencesRequest};
-use serde::Deserialize;
+use crate::audit_chain;
+use crate::auth::{bearer_token, AuthParticipant, Role, TokenClaims};
+use crate::ballots;
+use crate::models::{
+    BallotHistory, BallotReceipt, BallotVersion, JoinWentuRequest, JoinWentuResponse,
+    UpdatePreferencesRequest,
+};
+use crate::sanitize;
+
+/// A participant already registered under the name a join request asks for.
+#[derive(FromRow)]
+struct NamedParticipant {
+    id: Uuid,
+    is_creator: bool,
+    token_expires_at: DateTime<Utc>,
+    token_generation: i32,
+    /// Set when the creator revoked the participant
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+/// A name is not a credential: joining under a taken name only resumes that participant
+/// for a caller already holding a current token issued to them. Anyone else gets `409`,
+/// and a superseded token `401`. A participant revoked by the creator gets `403` whatever
+/// token they hold.
+fn resume_identity(
+    existing: &NamedParticipant,
+    wentu_id: Uuid,
+    claims: Option<&TokenClaims>,
+) -> Result<(), StatusCode> {
+    if existing.revoked_at.is_some() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    match claims {
+        Some(claims) if claims.wentu_id == wentu_id && claims.participant_id == existing.id => {
+            if claims.gen == existing.token_generation {
+                Ok(())
+            } else {
+                Err(StatusCode::UNAUTHORIZED)
+            }
+        }
+        _ => Err(StatusCode::CONFLICT),
+    }
+}
 
 /// Join an existing wentu
 pub async fn join_wentu(
     State(state): State<AppState>,
     Path(slug): Path<String>,
+    headers: HeaderMap,
     Json(req): Json<JoinWentuRequest>,
 ) -> Result<(StatusCode, Json<JoinWentuResponse>), StatusCode> {
     // Validate input
     req.validate().map_err(|e| {
-        tracing::warn!("Validation failed for join_wentu: {:?}", e);
+        tracing::warn!(
+            "Validation failed for join_wentu: {:?}",
+            super::invalid_fields(&e)
+        );
         StatusCode::BAD_REQUEST
     })?;
 
-    // Sanitize input
-    let name = req.name.trim().to_string();
-
-    if name.is_empty() {
-        tracing::warn!("Empty name after trimming");
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
-    if !SAFE_NAME_REGEX.is_match(&name) {
-        tracing::warn!("Invalid characters in participant name");
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Fetch wentu
-    let wentu_row = sqlx::query("SELECT id FROM wentus WHERE slug = $1")
-        .bind(&slug)
-        .fetch_optional(&state.db)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let wentu_row =
+        sqlx::query("SELECT id, access_passphrase_hash, invite_only FROM wentus WHERE slug = $1")
+            .bind(&slug)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
 
     let wentu_id: Uuid = wentu_row.get(0);
+    let access_passphrase_hash: Option<String> = wentu_row.get(1);
+    let invite_only: bool = wentu_row.get(2);
+
+    access::require_access(
+        &state,
+        wentu_id,
+        &slug,
+        access_passphrase_hash.as_deref(),
+        &headers,
+    )
+    .await?;
+
+    // Invite-only wentus never fall back to joining by name
+    if invite_only {
+        let invite_token = req.invite_token.as_deref().ok_or_else(|| {
+            tracing::warn!("join_wentu without invite on invite-only wentu {}", slug);
+            StatusCode::FORBIDDEN
+        })?;
+        return join_with_invite(&state, wentu_id, &slug, invite_token).await;
+    }
+
+    // Sanitize input
+    let name = req.name.as_deref().ok_or_else(|| {
+        tracing::warn!("join_wentu without a name on {}", slug);
+        StatusCode::BAD_REQUEST
+    })?;
+    let name = sanitize::sanitize_name(name).map_err(|e| {
+        tracing::warn!("Invalid participant name: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
 
-    let existing = sqlx::query(
-        "SELECT p.id, p.participant_key, p.token_expires_at, COUNT(r.id) AS ranking_count
+    let existing = sqlx::query_as::<_, NamedParticipant>(
+        "SELECT p.id, p.is_creator, p.token_expires_at, p.token_generation, p.revoked_at
          FROM participants p
-         LEFT JOIN rankings r ON r.participant_id = p.id
          WHERE p.wentu_id = $1 AND LOWER(p.name) = LOWER($2)
-         GROUP BY p.id
-         ORDER BY ranking_count DESC, p.joined_at ASC
+         ORDER BY p.ballot_version > 0 DESC, p.joined_at ASC
          LIMIT 1",
     )
     .bind(wentu_id)
@@ -66,32 +127,30 @@ pub async fn join_wentu(
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    if let Some(row) = existing {
-        let participant_id: Uuid = row.get("id");
-        let participant_key: String = row.get("participant_key");
-        let token_expires_at: DateTime<Utc> = row.get("token_expires_at");
-
-        if token_expires_at < Utc::now() {
-            let refreshed = Utc::now() + chrono::Duration::days(7);
-            sqlx::query("UPDATE participants SET token_expires_at = $1 WHERE id = $2")
-                .bind(refreshed)
-                .bind(participant_id)
-                .execute(&state.db)
-                .await
-                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        }
+    if let Some(existing) = existing {
+        let claims = bearer_token(&headers).and_then(|token| state.tokens.verify(token).ok());
+        resume_identity(&existing, wentu_id, claims.as_ref()).inspect_err(|status| {
+            tracing::warn!("join_wentu: name already taken on {} ({})", slug, status);
+        })?;
 
         let response = JoinWentuResponse {
-            participant_id,
-            participant_key: participant_key.clone(),
+            participant_id: existing.id,
+            token: state.tokens.issue(&TokenClaims::new(
+                wentu_id,
+                existing.id,
+                Role::from_is_creator(existing.is_creator),
+                existing.token_expires_at,
+                existing.token_generation,
+            )),
+            token_expires_at: existing.token_expires_at,
         };
 
         audit::log_action(
             &state.db,
             "JOIN_WENTU",
             "participant",
-            Some(participant_id),
-            Some(&participant_key),
+            Some(existing.id),
+            Some(audit::Actor::participant(existing.id)),
             Some(json!({ "slug": slug, "name": name })),
             true,
         )
@@ -99,19 +158,38 @@ pub async fn join_wentu(
 
         return Ok((StatusCode::OK, Json(response)));
     }
+
+    // Refuse names that merely look like an existing participant's ("Bob" vs Cyrillic "Воb")
+    let skeleton = sanitize::skeleton(&name);
+    let existing_names =
+        sqlx::query_scalar::<_, String>("SELECT name FROM participants WHERE wentu_id = $1")
+            .bind(wentu_id)
+            .fetch_all(&state.db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if existing_names
+        .iter()
+        .any(|existing| sanitize::skeleton(existing) == skeleton)
+    {
+        tracing::warn!(
+            "join_wentu: name confusable with an existing participant on {}",
+            slug
+        );
+        return Err(StatusCode::CONFLICT);
+    }
+
     let participant_id = Uuid::new_v4();
-    let participant_key = Uuid::new_v4().to_string();
-    let token_expires_at = Utc::now() + chrono::Duration::days(7);
+    let token_expires_at = Utc::now() + state.settings.token_ttl();
 
     // Insert participant
     sqlx::query(
-        "INSERT INTO participants (id, wentu_id, name, participant_key, is_creator, joined_at, token_expires_at)
-         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        "INSERT INTO participants (id, wentu_id, name, is_creator, joined_at, token_expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6)",
     )
     .bind(participant_id)
     .bind(wentu_id)
     .bind(&name)
-    .bind(&participant_key)
     .bind(false)
     .bind(Utc::now())
     .bind(token_expires_at)
@@ -121,7 +199,14 @@ pub async fn join_wentu(
 
     let response = JoinWentuResponse {
         participant_id,
-        participant_key: participant_key.clone(),
+        token: state.tokens.issue(&TokenClaims::new(
+            wentu_id,
+            participant_id,
+            Role::Participant,
+            token_expires_at,
+            0,
+        )),
+        token_expires_at,
     };
 
     audit::log_action(
@@ -129,7 +214,7 @@ pub async fn join_wentu(
         "JOIN_WENTU",
         "participant",
         Some(participant_id),
-        Some(&participant_key),
+        Some(audit::Actor::participant(participant_id)),
         Some(json!({ "slug": slug, "name": name })),
         true,
     )
@@ -138,15 +223,131 @@ pub async fn join_wentu(
     Ok((StatusCode::CREATED, Json(response)))
 }
 
+/// Redeem a single-use invite, creating the invitee's participant, or resuming it when the
+/// creator reissued the invite after it had been used
+async fn join_with_invite(
+    state: &AppState,
+    wentu_id: Uuid,
+    slug: &str,
+    invite_token: &str,
+) -> Result<(StatusCode, Json<JoinWentuResponse>), StatusCode> {
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let invite = sqlx::query(
+        "UPDATE invites SET used_at = NOW()
+         WHERE wentu_id = $1 AND token_hash = $2 AND used_at IS NULL
+         RETURNING id, name, participant_id",
+    )
+    .bind(wentu_id)
+    .bind(invite::hash_invite_token(invite_token))
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(invite) = invite else {
+        tracing::warn!("join_wentu with invalid or used invite on {}", slug);
+        audit::log_action(
+            &state.db,
+            "JOIN_WENTU",
+            "wentu",
+            Some(wentu_id),
+            None,
+            Some(json!({ "slug": slug, "reason": "invalid_invite" })),
+            false,
+        )
+        .await;
+        return Err(StatusCode::FORBIDDEN);
+    };
+
+    let invite_id: Uuid = invite.get("id");
+    let name: String = invite.get("name");
+    let token_expires_at = Utc::now() + state.settings.token_ttl();
+
+    if let Some(participant_id) = invite.get::<Option<Uuid>, _>("participant_id") {
+        return resume_with_invite(
+            state,
+            tx,
+            wentu_id,
+            slug,
+            participant_id,
+            invite_id,
+            token_expires_at,
+        )
+        .await;
+    }
+
+    let participant_id = Uuid::new_v4();
+
+    sqlx::query(
+        "INSERT INTO participants (id, wentu_id, name, is_creator, joined_at, token_expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(participant_id)
+    .bind(wentu_id)
+    .bind(&name)
+    .bind(false)
+    .bind(Utc::now())
+    .bind(token_expires_at)
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    sqlx::query("UPDATE invites SET participant_id = $1 WHERE id = $2")
+        .bind(participant_id)
+        .bind(invite_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    audit::log_action(
+        &state.db,
+        "JOIN_WENTU",
+        "participant",
+        Some(participant_id),
+        Some(audit::Actor::participant(participant_id)),
+        Some(json!({ "slug": slug, "name": name, "invite_id": invite_id })),
+        true,
+    )
+    .await;
+
+    let response = JoinWentuResponse {
+        participant_id,
+        token: state.tokens.issue(&TokenClaims::new(
+            wentu_id,
+            participant_id,
+            Role::Participant,
+            token_expires_at,
+            0,
+        )),
+        token_expires_at,
+    };
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
 /// Update participant preferences
 pub async fn update_preferences(
     State(state): State<AppState>,
     Path(slug): Path<String>,
+    headers: HeaderMap,
     Json(req): Json<UpdatePreferencesRequest>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<Json<BallotReceipt>, StatusCode> {
+    let auth = AuthParticipant::from_headers(&state, &headers).await?;
+
     // Validate payload
     req.validate().map_err(|e| {
-        tracing::warn!("Validation failed for update_preferences: {:?}", e);
+        tracing::warn!(
+            "Validation failed for update_preferences: {:?}",
+            super::invalid_fields(&e)
+        );
         StatusCode::BAD_REQUEST
     })?;
 
@@ -164,95 +365,384 @@ pub async fn update_preferences(
         }
     }
 
-    // Verify participant and wentu exist
+    // Verify participant still exists for this wentu
     let participant_row = sqlx::query(
-        "SELECT p.wentu_id, p.token_expires_at FROM participants p
+        "SELECT p.token_expires_at, w.anonymous, w.ballot_salt FROM participants p
          JOIN wentus w ON p.wentu_id = w.id
-         WHERE p.id = $1 AND w.slug = $2 AND p.participant_key = $3",
+         WHERE p.id = $1 AND w.id = $2 AND w.slug = $3",
     )
-    .bind(req.participant_id)
+    .bind(auth.participant_id)
+    .bind(auth.wentu_id)
     .bind(&slug)
-    .bind(&req.participant_key)
     .fetch_optional(&state.db)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
     .ok_or(StatusCode::UNAUTHORIZED)?;
 
-    let wentu_id: Uuid = participant_row.get(0);
-    let token_expires_at: DateTime<Utc> = participant_row.get(1);
+    let token_expires_at: DateTime<Utc> = participant_row.get(0);
+    let anonymous: bool = participant_row.get(1);
+    let ballot_salt: String = participant_row.get(2);
+
+    // Request logs of an anonymous ballot must not name the voter
+    if !anonymous {
+        auth.record_span();
+    }
 
     if token_expires_at < Utc::now() {
         tracing::warn!(
             "update_preferences blocked: token expired for participant {}",
-            req.participant_id
+            auth.participant_id
         );
         return Err(StatusCode::UNAUTHORIZED);
     }
 
-    // Delete old rankings
-    sqlx::query("DELETE FROM rankings WHERE participant_id = $1")
-        .bind(req.participant_id)
-        .execute(&state.db)
+    let mut ranked: Vec<_> = req.rankings.iter().collect();
+    ranked.sort_by_key(|ranking| ranking.preference_order);
+    let ranking: Vec<Uuid> = ranked
+        .iter()
+        .map(|ranking| ranking.date_option_id)
+        .collect();
+    let recorded_at = Utc::now();
+
+    let (ballot_id, version) = if anonymous {
+        cast_anonymous_ballot(&state, auth.wentu_id, auth.participant_id, &slug, &req).await?
+    } else {
+        // Replace the ballot atomically so a receipt never describes a half-written one
+        let mut tx = state
+            .db
+            .begin()
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        // Delete old rankings
+        sqlx::query("DELETE FROM rankings WHERE participant_id = $1")
+            .bind(auth.participant_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        // Insert new rankings
+        for ranking in &req.rankings {
+            sqlx::query(
+                "INSERT INTO rankings (participant_id, date_option_id, preference_order)
+                 VALUES ($1, $2, $3)",
+            )
+            .bind(auth.participant_id)
+            .bind(ranking.date_option_id)
+            .bind(ranking.preference_order)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        }
+
+        let version = sqlx::query_scalar::<_, i32>(
+            "UPDATE participants SET ballot_version = ballot_version + 1
+             WHERE id = $1
+             RETURNING ballot_version",
+        )
+        .bind(auth.participant_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        sqlx::query(
+            "INSERT INTO ballot_history (participant_id, wentu_id, version, recorded_at, ranking)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(auth.participant_id)
+        .bind(auth.wentu_id)
+        .bind(version)
+        .bind(recorded_at)
+        .bind(&ranking)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        // Lets the audit chain prove later that the stored ballot is the one submitted; written
+        // in the same transaction so no ballot is ever stored without its chain entry
+        let ballot_digest = audit_chain::ballot_digest(
+            &ballot_salt,
+            &req.rankings
+                .iter()
+                .map(|ranking| (ranking.date_option_id, ranking.preference_order))
+                .collect::<Vec<_>>(),
+        );
+        audit::log_action_in(
+            &mut tx,
+            "UPDATE_PREFERENCES",
+            "participant",
+            Some(auth.participant_id),
+            Some(audit::Actor::participant(auth.participant_id)),
+            Some(json!({
+                "slug": slug,
+                "rankings": req.rankings.len(),
+                "ballot_digest": ballot_digest,
+                "ballot_version": version,
+            })),
+            true,
+        )
+        .await
+        .map_err(|err| {
+            tracing::error!("Failed to write ballot audit entry: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        invalidate_results(&mut tx, auth.wentu_id).await?;
+
+        tx.commit()
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        (
+            ballots::ballot_id(&ballot_salt, auth.participant_id, version),
+            version,
+        )
+    };
+
+    Ok(Json(BallotReceipt {
+        ballot_hash: ballots::receipt_hash(&ballot_id, &ranking),
+        ballot_id,
+        version,
+        recorded_at,
+    }))
+}
+
+/// Invalidates cached results for the wentu
+async fn invalidate_results(conn: &mut PgConnection, wentu_id: Uuid) -> Result<(), StatusCode> {
+    sqlx::query("UPDATE wentus SET ballots_version = ballots_version + 1 WHERE id = $1")
+        .bind(wentu_id)
+        .execute(conn)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(())
+}
+
+/// Store a ballot for an anonymous wentu under a fresh random id. Marking the participant
+/// as having voted happens first and only once, so a second ballot is refused with `409`:
+/// an unlinked ballot cannot be found again to replace it.
+///
+/// Nothing the API returns or logs ties the ballot to the voter. The database does not
+/// hide it from someone reading it directly: the mark, the ballot and its audit entry are
+/// committed one after the other, so transaction ids and row order follow submission order.
+async fn cast_anonymous_ballot(
+    state: &AppState,
+    wentu_id: Uuid,
+    participant_id: Uuid,
+    slug: &str,
+    req: &UpdatePreferencesRequest,
+) -> Result<(String, i32), StatusCode> {
+    // Checked before marking the participant, so a bad request cannot use up their ballot
+    let option_ids: Vec<Uuid> = req
+        .rankings
+        .iter()
+        .map(|ranking| ranking.date_option_id)
+        .collect();
+    let known = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM date_ranges WHERE wentu_id = $1 AND id = ANY($2)",
+    )
+    .bind(wentu_id)
+    .bind(&option_ids)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if known != option_ids.len() as i64 {
+        tracing::warn!("Anonymous ballot ranks a date option of another wentu");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let version = sqlx::query_scalar::<_, i32>(
+        "UPDATE participants SET ballot_version = 1
+         WHERE id = $1 AND ballot_version = 0
+         RETURNING ballot_version",
+    )
+    .bind(participant_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or_else(|| {
+        tracing::warn!("update_preferences blocked: anonymous ballot already cast");
+        StatusCode::CONFLICT
+    })?;
+
+    let ballot_id = Uuid::new_v4();
+    if let Err(status) = store_anonymous_ballot(state, wentu_id, ballot_id, slug, req).await {
+        // Nothing was counted; let the participant try again
+        if let Err(err) = sqlx::query("UPDATE participants SET ballot_version = 0 WHERE id = $1")
+            .bind(participant_id)
+            .execute(&state.db)
+            .await
+        {
+            tracing::error!("Failed to reopen anonymous ballot: {:?}", err);
+        }
+        return Err(status);
+    }
+
+    Ok((ballot_id.to_string(), version))
+}
+
+async fn store_anonymous_ballot(
+    state: &AppState,
+    wentu_id: Uuid,
+    ballot_id: Uuid,
+    slug: &str,
+    req: &UpdatePreferencesRequest,
+) -> Result<(), StatusCode> {
+    let mut tx = state
+        .db
+        .begin()
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    // Insert new rankings
     for ranking in &req.rankings {
         sqlx::query(
-            "INSERT INTO rankings (participant_id, date_option_id, preference_order)
-             VALUES ($1, $2, $3)",
+            "INSERT INTO anonymous_rankings (ballot_id, wentu_id, date_option_id, preference_order)
+             VALUES ($1, $2, $3, $4)",
         )
-        .bind(req.participant_id)
+        .bind(ballot_id)
+        .bind(wentu_id)
         .bind(ranking.date_option_id)
         .bind(ranking.preference_order)
-        .execute(&state.db)
+        .execute(&mut *tx)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     }
 
-    audit::log_action(
+    invalidate_results(&mut tx, wentu_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Names neither the voter nor anything about the ballot or the request
+    audit::log_unattributed(
         &state.db,
         "UPDATE_PREFERENCES",
+        "wentu",
+        Some(wentu_id),
+        Some(json!({ "slug": slug, "anonymous": true })),
+        true,
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Finish redeeming a reissued invite by handing its participant a token at their current
+/// generation. Participants the creator revoked stay out.
+async fn resume_with_invite(
+    state: &AppState,
+    mut tx: sqlx::Transaction<'_, sqlx::Postgres>,
+    wentu_id: Uuid,
+    slug: &str,
+    participant_id: Uuid,
+    invite_id: Uuid,
+    token_expires_at: DateTime<Utc>,
+) -> Result<(StatusCode, Json<JoinWentuResponse>), StatusCode> {
+    let token_generation = sqlx::query_scalar::<_, i32>(
+        "UPDATE participants SET token_expires_at = $2
+         WHERE id = $1 AND revoked_at IS NULL
+         RETURNING token_generation",
+    )
+    .bind(participant_id)
+    .bind(token_expires_at)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or_else(|| {
+        tracing::warn!(
+            "join_wentu: reissued invite for a revoked participant on {}",
+            slug
+        );
+        StatusCode::FORBIDDEN
+    })?;
+
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    audit::log_action(
+        &state.db,
+        "JOIN_WENTU",
         "participant",
-        Some(req.participant_id),
-        Some(&req.participant_key),
-        Some(json!({ "slug": slug, "rankings": req.rankings.len() })),
+        Some(participant_id),
+        Some(audit::Actor::participant(participant_id)),
+        Some(json!({ "slug": slug, "invite_id": invite_id, "resumed": true })),
         true,
     )
     .await;
 
-    Ok(StatusCode::OK)
+    let response = JoinWentuResponse {
+        participant_id,
+        token: state.tokens.issue(&TokenClaims::new(
+            wentu_id,
+            participant_id,
+            Role::Participant,
+            token_expires_at,
+            token_generation,
+        )),
+        token_expires_at,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
 }
 
-#[derive(Deserialize, Validate)]
-pub struct CheckVotedRequest {
-    pub participant_id: Uuid,
-    #[validate(length(min = 1))]
-    pub participant_key: String,
+/// The caller's own ballot versions, oldest first
+pub async fn get_ballot_history(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    auth: AuthParticipant,
+) -> Result<Json<BallotHistory>, StatusCode> {
+    auth.authorize_slug(&state.db, &slug).await?;
+
+    let rows = sqlx::query_as::<_, (i32, DateTime<Utc>, Vec<Uuid>, String)>(
+        "SELECT h.version, h.recorded_at, h.ranking, w.ballot_salt
+         FROM ballot_history h
+         JOIN wentus w ON w.id = h.wentu_id
+         WHERE h.participant_id = $1 AND h.wentu_id = $2
+         ORDER BY h.version",
+    )
+    .bind(auth.participant_id)
+    .bind(auth.wentu_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to fetch ballot history: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let versions = rows
+        .into_iter()
+        .map(|(version, recorded_at, ranking, ballot_salt)| {
+            let ballot_id = ballots::ballot_id(&ballot_salt, auth.participant_id, version);
+            BallotVersion {
+                version,
+                recorded_at,
+                ballot_hash: ballots::receipt_hash(&ballot_id, &ranking),
+                ballot_id,
+                ranking,
+            }
+        })
+        .collect();
+
+    Ok(Json(BallotHistory { versions }))
 }
 
 /// Check if participant has voted
 pub async fn has_voted(
     State(state): State<AppState>,
     Path(slug): Path<String>,
-    Json(req): Json<CheckVotedRequest>,
+    auth: AuthParticipant,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    // Validate input
-    req.validate().map_err(|e| {
-        tracing::warn!("Validation failed for has_voted: {:?}", e);
-        StatusCode::BAD_REQUEST
-    })?;
-
     // Verify participant and wentu exist
     let participant_row = sqlx::query(
         "SELECT p.id, p.is_creator
          FROM participants p
          JOIN wentus w ON p.wentu_id = w.id
-         WHERE p.id = $1 AND w.slug = $2 AND p.participant_key = $3",
+         WHERE p.id = $1 AND w.id = $2 AND w.slug = $3",
     )
-    .bind(req.participant_id)
+    .bind(auth.participant_id)
+    .bind(auth.wentu_id)
     .bind(&slug)
-    .bind(&req.participant_key)
     .fetch_optional(&state.db)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
@@ -260,14 +750,13 @@ pub async fn has_voted(
 
     let is_creator: bool = participant_row.get(1);
 
-    // Check if participant has any rankings
-    let has_rankings = sqlx::query_scalar::<_, bool>(
-        "SELECT EXISTS(SELECT 1 FROM rankings WHERE participant_id = $1)",
-    )
-    .bind(req.participant_id)
-    .fetch_one(&state.db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Anonymous ballots are not linked to the participant, so check the has-voted marker
+    let has_rankings =
+        sqlx::query_scalar::<_, bool>("SELECT ballot_version > 0 FROM participants WHERE id = $1")
+            .bind(auth.participant_id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     Ok(Json(json!({
         "has_voted": has_rankings,
@@ -279,55 +768,59 @@ pub async fn has_voted(
 pub async fn get_voters(
     State(state): State<AppState>,
     Path(slug): Path<String>,
-    Json(req): Json<CheckVotedRequest>,
+    auth: AuthParticipant,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    // Validate input
-    req.validate().map_err(|e| {
-        tracing::warn!("Validation failed for get_voters: {:?}", e);
-        StatusCode::BAD_REQUEST
-    })?;
-
-    // Verify participant and wentu exist, and check if creator
-    let participant_row = sqlx::query(
-        "SELECT p.id, p.is_creator
-         FROM participants p
-         JOIN wentus w ON p.wentu_id = w.id
-         WHERE p.id = $1 AND w.slug = $2 AND p.participant_key = $3",
-    )
-    .bind(req.participant_id)
-    .bind(&slug)
-    .bind(&req.participant_key)
-    .fetch_optional(&state.db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    let is_creator: bool = participant_row.get(1);
-
     // Only creators can see the voter list
-    if !is_creator {
+    if !auth.is_creator() {
         return Err(StatusCode::FORBIDDEN);
     }
 
-    // Get wentu ID
-    let wentu_row = sqlx::query("SELECT id FROM wentus WHERE slug = $1")
-        .bind(&slug)
-        .fetch_optional(&state.db)
+    // Verify the token belongs to this wentu
+    auth.authorize_slug(&state.db, &slug).await?;
+
+    let (invite_only, anonymous) = sqlx::query_as::<_, (bool, bool)>(
+        "SELECT invite_only, anonymous FROM wentus WHERE id = $1",
+    )
+    .bind(auth.wentu_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Anonymous wentus only reveal how many have voted, not who
+    if anonymous {
+        let (voters_count, not_responded_count) = sqlx::query_as::<_, (i64, i64)>(
+            "SELECT
+                 (SELECT COUNT(*) FROM participants
+                  WHERE wentu_id = $1 AND ballot_version > 0),
+                 (SELECT COUNT(*) FROM invites i
+                  WHERE i.wentu_id = $1
+                    AND NOT EXISTS (SELECT 1 FROM participants p
+                                    WHERE p.id = i.participant_id AND p.ballot_version > 0))",
+        )
+        .bind(auth.wentu_id)
+        .fetch_one(&state.db)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let wentu_id: Uuid = wentu_row.get(0);
+        if !invite_only {
+            return Ok(Json(json!({
+                "voters_count": voters_count,
+            })));
+        }
+        return Ok(Json(json!({
+            "voters_count": voters_count,
+            "not_responded_count": not_responded_count,
+        })));
+    }
 
     // Get list of participants who have voted
     let voters = sqlx::query(
-        "SELECT DISTINCT p.name
+        "SELECT p.name
          FROM participants p
-         INNER JOIN rankings r ON p.id = r.participant_id
-         WHERE p.wentu_id = $1
+         WHERE p.wentu_id = $1 AND p.ballot_version > 0
          ORDER BY p.name",
     )
-    .bind(wentu_id)
+    .bind(auth.wentu_id)
     .fetch_all(&state.db)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
@@ -335,7 +828,138 @@ pub async fn get_voters(
     .map(|row| row.get::<String, _>(0))
     .collect::<Vec<_>>();
 
+    if !invite_only {
+        return Ok(Json(json!({
+            "voters": voters,
+        })));
+    }
+
+    // Invitees who have not joined yet, or joined without ranking anything
+    let not_responded = sqlx::query_scalar::<_, String>(
+        "SELECT i.name
+         FROM invites i
+         WHERE i.wentu_id = $1
+           AND NOT EXISTS (SELECT 1 FROM participants p
+                           WHERE p.id = i.participant_id AND p.ballot_version > 0)
+         ORDER BY i.name",
+    )
+    .bind(auth.wentu_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
     Ok(Json(json!({
         "voters": voters,
+        "not_responded": not_responded,
     })))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(token_generation: i32) -> NamedParticipant {
+        NamedParticipant {
+            id: Uuid::new_v4(),
+            is_creator: true,
+            token_expires_at: Utc::now() + chrono::Duration::days(7),
+            token_generation,
+            revoked_at: None,
+        }
+    }
+
+    fn claims(wentu_id: Uuid, participant: &NamedParticipant) -> TokenClaims {
+        TokenClaims::new(
+            wentu_id,
+            participant.id,
+            Role::Creator,
+            participant.token_expires_at,
+            participant.token_generation,
+        )
+    }
+
+    #[test]
+    fn test_name_alone_does_not_resume_identity() {
+        let wentu_id = Uuid::new_v4();
+        let creator = named(0);
+        let other = named(0);
+
+        assert_eq!(
+            resume_identity(&creator, wentu_id, None),
+            Err(StatusCode::CONFLICT)
+        );
+        assert_eq!(
+            resume_identity(&creator, wentu_id, Some(&claims(wentu_id, &other))),
+            Err(StatusCode::CONFLICT)
+        );
+        assert_eq!(
+            resume_identity(&creator, wentu_id, Some(&claims(Uuid::new_v4(), &creator))),
+            Err(StatusCode::CONFLICT)
+        );
+        assert_eq!(
+            resume_identity(&creator, wentu_id, Some(&claims(wentu_id, &creator))),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_rejoin_after_revoke_is_refused() {
+        let wentu_id = Uuid::new_v4();
+        let mut participant = named(0);
+        let before_revoke = claims(wentu_id, &participant);
+
+        // `revoke_sessions` bumps the generation
+        participant.token_generation += 1;
+
+        assert_eq!(
+            resume_identity(&participant, wentu_id, Some(&before_revoke)),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            resume_identity(&participant, wentu_id, None),
+            Err(StatusCode::CONFLICT)
+        );
+    }
+
+    #[test]
+    fn test_revoked_participant_cannot_resume_by_name() {
+        let wentu_id = Uuid::new_v4();
+        let mut participant = named(0);
+        participant.is_creator = false;
+
+        // `revoke_participant` also stamps `revoked_at`; even a token at the current
+        // generation no longer resumes them
+        participant.revoked_at = Some(Utc::now());
+        let current = claims(wentu_id, &participant);
+
+        assert_eq!(
+            resume_identity(&participant, wentu_id, Some(&current)),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            resume_identity(&participant, wentu_id, None),
+            Err(StatusCode::FORBIDDEN)
+        );
+    }
+
+    /// History is read for the participant named in the caller's token, so reading someone
+    /// else's would take a token for them; rejoining under their name must not hand one out.
+    #[test]
+    fn test_cannot_obtain_another_participants_history() {
+        let wentu_id = Uuid::new_v4();
+        let alice = named(0);
+        let mut bob = named(0);
+        bob.is_creator = false;
+        let bobs_token = claims(wentu_id, &bob);
+
+        assert_eq!(
+            resume_identity(&alice, wentu_id, Some(&bobs_token)),
+            Err(StatusCode::CONFLICT)
+        );
+        assert_eq!(
+            resume_identity(&alice, wentu_id, None),
+            Err(StatusCode::CONFLICT)
+        );
+        assert_eq!(resume_identity(&bob, wentu_id, Some(&bobs_token)), Ok(()));
+    }
+}
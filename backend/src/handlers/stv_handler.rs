@@ -1,97 +1,185 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use sqlx::{FromRow, Row};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::Row;
+use std::sync::Arc;
+use std::time::Instant;
 use uuid::Uuid;
 
 use super::AppState;
-use crate::stv::{calculate_stv, STVResult};
+use crate::access::{self, ResultsPolicy};
+use crate::ballots;
+use crate::results_cache::CountedResults;
+use crate::stv::calculate_stv;
+use crate::stv::explain::explain;
+use crate::telemetry;
 
-#[derive(FromRow)]
-struct ParticipantPreferences {
-    participant_id: Uuid,
-    date_option_id: Uuid,
-    preference_order: i32,
+/// A wentu whose results are being published.
+struct ResultsWentu {
+    id: Uuid,
+    title: String,
+    anonymous: bool,
+    /// Bumped on every ballot change; keys the results cache
+    ballots_version: i64,
+    /// Keys the published ballot ids
+    ballot_salt: String,
+    policy: ResultsPolicy,
 }
 
-#[derive(FromRow)]
-struct DateOptionInfo {
-    id: Uuid,
+/// Look up a wentu by slug and check the caller may see it.
+async fn load_wentu(
+    state: &AppState,
+    slug: &str,
+    headers: &HeaderMap,
+) -> Result<ResultsWentu, StatusCode> {
+    let wentu_row = sqlx::query(
+        "SELECT id, title, access_passphrase_hash, status::text, expires_at, pref_deadline,
+                results_visibility, anonymous, ballots_version, ballot_salt
+         FROM wentus WHERE slug = $1",
+    )
+    .bind(slug)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to fetch wentu: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let wentu_id: Uuid = wentu_row.get(0);
+    let access_passphrase_hash: Option<String> = wentu_row.get(2);
+    let status: String = wentu_row.get(3);
+    let expires_at: DateTime<Utc> = wentu_row.get(4);
+
+    access::require_access(
+        state,
+        wentu_id,
+        slug,
+        access_passphrase_hash.as_deref(),
+        headers,
+    )
+    .await?;
+
+    Ok(ResultsWentu {
+        id: wentu_id,
+        title: wentu_row.get(1),
+        anonymous: wentu_row.get(7),
+        ballots_version: wentu_row.get(8),
+        ballot_salt: wentu_row.get(9),
+        policy: ResultsPolicy {
+            visibility: wentu_row.get(6),
+            closed: status != "open" || Utc::now() > expires_at,
+            pref_deadline: wentu_row.get(5),
+        },
+    })
+}
+
+/// Count the wentu's current ballots, or those standing at `as_of`.
+async fn count_results(
+    state: &AppState,
+    wentu: &ResultsWentu,
+    as_of: Option<DateTime<Utc>>,
+) -> Result<CountedResults, StatusCode> {
+    let wentu_id = wentu.id;
+    let candidates = ballots::load_candidates(&state.db, wentu_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let date_options = candidates
+        .iter()
+        .map(|candidate| candidate.id)
+        .collect::<Vec<_>>();
+
+    let voter_preferences: Vec<Vec<Uuid>> = match as_of {
+        Some(as_of) => ballots::load_rankings_as_of(&state.db, wentu_id, as_of)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .into_values()
+            .collect(),
+        None => ballots::load_ballots(&state.db, wentu_id, &wentu.ballot_salt)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .into_iter()
+            .map(|ballot| ballot.ranking)
+            .collect(),
+    };
+    let total_voters = voter_preferences.len();
+
+    let started = Instant::now();
+    let result = calculate_stv(voter_preferences, date_options);
+    telemetry::record_stv_duration(started);
+
+    Ok(CountedResults {
+        explanation: explain(&result, &candidates, total_voters),
+        result,
+        total_voters,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResultsQuery {
+    /// Count the ballots as they stood at this time instead of now
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// The current count, from the cache when no ballot has changed since it was made, so every
+/// endpoint reports the same winner.
+async fn current_results(
+    state: &AppState,
+    wentu: &ResultsWentu,
+) -> Result<Arc<CountedResults>, StatusCode> {
+    if let Some(counted) = state.results.get(wentu.id, wentu.ballots_version) {
+        return Ok(counted);
+    }
+    let counted = Arc::new(count_results(state, wentu, None).await?);
+    state
+        .results
+        .insert(wentu.id, wentu.ballots_version, counted.clone());
+    Ok(counted)
 }
 
 /// Get STV results for a wentu
 pub async fn get_stv_results(
     State(state): State<AppState>,
     Path(slug): Path<String>,
+    Query(query): Query<ResultsQuery>,
+    headers: HeaderMap,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     tracing::info!("GET STV results for: {}", slug);
 
-    // Get wentu ID
-    let wentu_row = sqlx::query("SELECT id FROM wentus WHERE slug = $1")
-        .bind(&slug)
-        .fetch_optional(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to fetch wentu: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or(StatusCode::NOT_FOUND)?;
-
-    let wentu_id: Uuid = wentu_row.get(0);
+    let wentu = load_wentu(&state, &slug, &headers).await?;
+    access::require_results_visible(&state, wentu.id, wentu.policy, &headers).await?;
+    let wentu_id = wentu.id;
 
-    // Get all date options in order
-    let date_options = sqlx::query_as::<_, DateOptionInfo>(
-        "SELECT id FROM date_ranges WHERE wentu_id = $1 ORDER BY sort_order",
-    )
-    .bind(wentu_id)
-    .fetch_all(&state.db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    .into_iter()
-    .map(|r| r.id)
-    .collect::<Vec<_>>();
-
-    // Get all participant preferences
-    let preferences = sqlx::query_as::<_, ParticipantPreferences>(
-        "SELECT participant_id, date_option_id, preference_order 
-         FROM rankings 
-         WHERE date_option_id IN (SELECT id FROM date_ranges WHERE wentu_id = $1)
-         ORDER BY participant_id, preference_order",
-    )
-    .bind(wentu_id)
-    .fetch_all(&state.db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let counted = match query.as_of {
+        // Anonymous ballots carry no timestamps to rewind by
+        Some(_) if wentu.anonymous => {
+            tracing::warn!("as_of requested for anonymous wentu {}", slug);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        // Past counts are not cached
+        Some(as_of) => Arc::new(count_results(&state, &wentu, Some(as_of)).await?),
+        None => current_results(&state, &wentu).await?,
+    };
+    let result = &counted.result;
 
     // Get total participant count for this wentu
     let total_participants = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(DISTINCT id) FROM participants WHERE wentu_id = $1"
+        "SELECT COUNT(DISTINCT id) FROM participants
+         WHERE wentu_id = $1 AND ($2::timestamptz IS NULL OR joined_at <= $2)",
     )
     .bind(wentu_id)
+    .bind(query.as_of)
     .fetch_one(&state.db)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? as usize;
 
-    // Build voter preferences structure (participant -> ordered date options)
-    let mut voter_prefs: std::collections::HashMap<Uuid, Vec<Uuid>> =
-        std::collections::HashMap::new();
-    for pref in preferences {
-        voter_prefs
-            .entry(pref.participant_id)
-            .or_insert_with(Vec::new)
-            .push(pref.date_option_id);
-    }
-
-    // Calculate turnout (before consuming voter_prefs)
-    let total_voters = voter_prefs.len();
-
-    // Convert to list of preference vectors
-    let voter_preferences = voter_prefs.into_iter().map(|(_, prefs)| prefs).collect();
-
-    // Calculate STV
-    let result = calculate_stv(voter_preferences, date_options);
+    let total_voters = counted.total_voters;
     let turnout_percentage = if total_participants > 0 {
         (total_voters as f64 / total_participants as f64 * 100.0).round() as u32
     } else {
@@ -105,15 +193,113 @@ pub async fn get_stv_results(
         "total_voters": total_voters,
         "total_participants": total_participants,
         "turnout_percentage": turnout_percentage,
+        "as_of": query.as_of,
         "rounds_count": result.rounds.len(),
+        "explanation": counted.explanation,
         "rounds": result.rounds.iter().map(|round| {
             serde_json::json!({
                 "round_number": round.round_number,
                 "vote_counts": round.vote_counts,
                 "eliminated": round.eliminated,
+                "transfers": round.transfers,
+                "exhausted": round.exhausted,
             })
         }).collect::<Vec<_>>(),
     });
 
     Ok(Json(response))
 }
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BallotFormat {
+    #[default]
+    Json,
+    Blt,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportBallotsQuery {
+    #[serde(default)]
+    pub format: BallotFormat,
+}
+
+/// The wentu's candidates and anonymized ballots, as published.
+async fn published_ballots(
+    state: &AppState,
+    wentu: &ResultsWentu,
+) -> Result<(Vec<ballots::Candidate>, Vec<ballots::AnonymousBallot>), StatusCode> {
+    let candidates = ballots::load_candidates(&state.db, wentu.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let ballots = ballots::load_ballots(&state.db, wentu.id, &wentu.ballot_salt)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((candidates, ballots))
+}
+
+/// Export the anonymized ballots behind the STV results, as JSON or BLT, so the count can
+/// be rerun with an external tool
+pub async fn export_ballots(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Query(query): Query<ExportBallotsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let wentu = load_wentu(&state, &slug, &headers).await?;
+    access::require_results_visible(&state, wentu.id, wentu.policy, &headers).await?;
+    let (candidates, ballots) = published_ballots(&state, &wentu).await?;
+
+    match query.format {
+        BallotFormat::Blt => Ok((
+            [
+                (
+                    header::CONTENT_TYPE,
+                    "text/plain; charset=utf-8".to_string(),
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}.blt\"", slug),
+                ),
+            ],
+            ballots::to_blt(&wentu.title, &candidates, &ballots),
+        )
+            .into_response()),
+        BallotFormat::Json => {
+            // Same count as `stv-results`, rather than a recount that could break ties
+            // differently
+            let counted = current_results(&state, &wentu).await?;
+            Ok(Json(json!({
+                "title": wentu.title,
+                "seats": 1,
+                "candidates": candidates,
+                "ballots": ballots,
+                "winner": counted.result.winner,
+            }))
+            .into_response())
+        }
+    }
+}
+
+/// Check whether a receipt's ballot hash is in the published ballot set, i.e. the voter's
+/// ballot is counted exactly as they submitted it
+pub async fn lookup_ballot(
+    State(state): State<AppState>,
+    Path((slug, ballot_hash)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    // Membership in the ballot set is part of the results: anyone able to compute a hash
+    // could otherwise probe hidden ballots
+    let wentu = load_wentu(&state, &slug, &headers).await?;
+    access::require_results_visible(&state, wentu.id, wentu.policy, &headers).await?;
+    let (_, ballots) = published_ballots(&state, &wentu).await?;
+    let included = ballots
+        .iter()
+        .any(|ballot| ballot.hash.eq_ignore_ascii_case(ballot_hash.trim()));
+
+    Ok(Json(json!({
+        "ballot_hash": ballot_hash,
+        "included": included,
+    })))
+}
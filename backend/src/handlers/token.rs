@@ -0,0 +1,198 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::Row;
+use uuid::Uuid;
+
+use super::AppState;
+use crate::audit;
+use crate::auth::{bearer_token, AuthParticipant, Role, TokenClaims};
+use crate::models::TokenResponse;
+
+/// Exchange a token for a fresh one with a sliding expiry
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    let token = bearer_token(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // Expired tokens may be refreshed; revocation is enforced through the generation check
+    let claims = state.tokens.verify_signature(token).map_err(|e| {
+        tracing::warn!("refresh_token rejected token for slug {}: {}", slug, e);
+        StatusCode::UNAUTHORIZED
+    })?;
+
+    let row = sqlx::query(
+        "SELECT p.is_creator, p.token_generation, w.expires_at
+         FROM participants p
+         JOIN wentus w ON p.wentu_id = w.id
+         WHERE p.id = $1 AND w.id = $2 AND w.slug = $3",
+    )
+    .bind(claims.participant_id)
+    .bind(claims.wentu_id)
+    .bind(&slug)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let is_creator: bool = row.get("is_creator");
+    let token_generation: i32 = row.get("token_generation");
+    let wentu_expires_at: DateTime<Utc> = row.get("expires_at");
+
+    if token_generation != claims.gen {
+        tracing::warn!(
+            "refresh_token blocked: revoked token for participant {}",
+            claims.participant_id
+        );
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let now = Utc::now();
+    if wentu_expires_at <= now {
+        tracing::info!("refresh_token blocked: wentu {} expired", slug);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // Never outlive the wentu itself
+    let token_expires_at = (now + state.settings.token_ttl()).min(wentu_expires_at);
+
+    sqlx::query("UPDATE participants SET token_expires_at = $1 WHERE id = $2")
+        .bind(token_expires_at)
+        .bind(claims.participant_id)
+        .execute(&state.db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    audit::log_action(
+        &state.db,
+        "REFRESH_TOKEN",
+        "participant",
+        Some(claims.participant_id),
+        Some(audit::Actor::participant(claims.participant_id)),
+        Some(json!({ "slug": slug })),
+        true,
+    )
+    .await;
+
+    Ok(Json(TokenResponse {
+        participant_id: claims.participant_id,
+        token: state.tokens.issue(&TokenClaims::new(
+            claims.wentu_id,
+            claims.participant_id,
+            Role::from_is_creator(is_creator),
+            token_expires_at,
+            token_generation,
+        )),
+        token_expires_at,
+    }))
+}
+
+/// Revoke every token issued to the caller, including the one used for this request, and
+/// return a replacement at the new generation so the caller keeps their own access
+pub async fn revoke_sessions(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    auth: AuthParticipant,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    auth.authorize_slug(&state.db, &slug).await?;
+
+    let wentu_expires_at =
+        sqlx::query_scalar::<_, DateTime<Utc>>("SELECT expires_at FROM wentus WHERE id = $1")
+            .bind(auth.wentu_id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let token_expires_at = (Utc::now() + state.settings.token_ttl()).min(wentu_expires_at);
+
+    let (is_creator, token_generation) = sqlx::query_as::<_, (bool, i32)>(
+        "UPDATE participants
+         SET token_generation = token_generation + 1, token_expires_at = $2
+         WHERE id = $1
+         RETURNING is_creator, token_generation",
+    )
+    .bind(auth.participant_id)
+    .bind(token_expires_at)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    tracing::info!("Revoked sessions for participant {}", auth.participant_id);
+    audit::log_action(
+        &state.db,
+        "REVOKE_SESSIONS",
+        "participant",
+        Some(auth.participant_id),
+        Some(audit::Actor::participant(auth.participant_id)),
+        Some(json!({ "slug": slug })),
+        true,
+    )
+    .await;
+
+    Ok(Json(TokenResponse {
+        participant_id: auth.participant_id,
+        token: state.tokens.issue(&TokenClaims::new(
+            auth.wentu_id,
+            auth.participant_id,
+            Role::from_is_creator(is_creator),
+            token_expires_at,
+            token_generation,
+        )),
+        token_expires_at,
+    }))
+}
+
+/// Revoke a participant's credentials and block them from re-joining (creator only)
+pub async fn revoke_participant(
+    State(state): State<AppState>,
+    Path((slug, participant_id)): Path<(String, Uuid)>,
+    auth: AuthParticipant,
+) -> Result<StatusCode, StatusCode> {
+    if !auth.is_creator() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    auth.authorize_slug(&state.db, &slug).await?;
+
+    if participant_id == auth.participant_id {
+        tracing::warn!(
+            "revoke_participant: creator tried to revoke themselves on {}",
+            slug
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let revoked = sqlx::query(
+        "UPDATE participants
+         SET token_generation = token_generation + 1, revoked_at = NOW()
+         WHERE id = $1 AND wentu_id = $2 AND NOT is_creator",
+    )
+    .bind(participant_id)
+    .bind(auth.wentu_id)
+    .execute(&state.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .rows_affected();
+
+    if revoked == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    tracing::info!("Participant {} revoked on wentu {}", participant_id, slug);
+    audit::log_action(
+        &state.db,
+        "REVOKE_PARTICIPANT",
+        "participant",
+        Some(participant_id),
+        Some(audit::Actor::participant(auth.participant_id)),
+        Some(json!({ "slug": slug })),
+        true,
+    )
+    .await;
+
+    Ok(StatusCode::OK)
+}
@@ -1,26 +1,45 @@
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     Json,
 };
 use chrono::Duration;
 use chrono::{DateTime, TimeZone, Utc};
 use chrono_tz::Tz;
+use metrics_exporter_prometheus::PrometheusHandle;
 use serde_json::json;
-use sqlx::{FromRow, PgPool, Row};
+use sqlx::{FromRow, PgPool};
 use std::str::FromStr;
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
 use uuid::Uuid;
 use validator::Validate;
 
+use super::invite;
+use crate::access::{self, ResultsPolicy};
 use crate::audit;
-use crate::models::wentu::{SAFE_NAME_REGEX, SAFE_TITLE_REGEX};
+use crate::auth::{AuthParticipant, Role, TokenClaims, TokenSigner};
+use crate::health::Heartbeats;
 use crate::models::{
-    CloseWentuRequest, CreateWentuRequest, CreateWentuResponse, DateRange, Wentu, WentuStatus,
+    CreateWentuRequest, CreateWentuResponse, DateRange, ResultsVisibility, Wentu, WentuStatus,
 };
+use crate::rate_limit::KeyedLimits;
+use crate::results_cache::ResultsCache;
+use crate::sanitize;
+use crate::settings::Settings;
+use crate::shutdown::Shutdown;
+use crate::slug;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
+    pub tokens: TokenSigner,
+    pub settings: Arc<Settings>,
+    pub limits: KeyedLimits,
+    pub results: ResultsCache,
+    pub metrics: PrometheusHandle,
+    pub heartbeats: Heartbeats,
+    pub shutdown: Shutdown,
 }
 
 #[derive(FromRow)]
@@ -30,13 +49,16 @@ struct WentuRow {
     title: String,
     description: Option<String>,
     creator_name: String,
-    creator_key: String,
     created_at: DateTime<Utc>,
     expires_at: DateTime<Utc>,
     pref_deadline: DateTime<Utc>,
     #[sqlx(rename = "status")]
     status_str: String,
     timezone: Option<String>,
+    access_passphrase_hash: Option<String>,
+    invite_only: bool,
+    anonymous: bool,
+    results_visibility: ResultsVisibility,
 }
 
 #[derive(FromRow)]
@@ -54,7 +76,7 @@ pub async fn create_wentu(
 ) -> Result<(StatusCode, Json<CreateWentuResponse>), StatusCode> {
     // Validate input
     req.validate().map_err(|e| {
-        tracing::warn!("Validation failed: {:?}", e);
+        tracing::warn!("Validation failed: {:?}", super::invalid_fields(&e));
         StatusCode::BAD_REQUEST
     })?;
 
@@ -65,59 +87,111 @@ pub async fn create_wentu(
     }
 
     let range_days = (req.date_range_end - req.date_range_start).num_days();
-    if range_days > 365 {
+    if range_days > state.settings.max_date_range_days {
         tracing::warn!("Date range too large: {} days", range_days);
         return Err(StatusCode::BAD_REQUEST);
     }
 
     // Sanitize inputs
-    let title = req.title.trim().to_string();
-    let creator_name = req.creator_name.trim().to_string();
-    let description = req.description.as_ref().map(|d| d.trim().to_string());
+    let title = sanitize::sanitize_title(&req.title).map_err(|e| {
+        tracing::warn!("Invalid title: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let creator_name = sanitize::sanitize_name(&req.creator_name).map_err(|e| {
+        tracing::warn!("Invalid creator name: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let description = req
+        .description
+        .as_ref()
+        .map(|d| d.trim().nfc().collect::<String>());
+
+    let vanity_slug = match req.slug.as_deref() {
+        Some(requested) => Some(slug::validate_vanity(requested).ok_or_else(|| {
+            tracing::warn!("Invalid vanity slug requested");
+            StatusCode::BAD_REQUEST
+        })?),
+        None => None,
+    };
 
-    if !SAFE_TITLE_REGEX.is_match(&title) {
-        tracing::warn!("Invalid characters in title");
-        return Err(StatusCode::BAD_REQUEST);
-    }
+    let invitees = invite::normalize_invitee_names(req.invitees.as_deref().unwrap_or_default())?;
+    let invite_only = req.invite_only.unwrap_or(false) || !invitees.is_empty();
+    let anonymous = req.anonymous.unwrap_or(false);
+    let results_visibility = req.results_visibility.unwrap_or_default();
 
-    if !SAFE_NAME_REGEX.is_match(&creator_name) {
-        tracing::warn!("Invalid characters in creator name");
-        return Err(StatusCode::BAD_REQUEST);
-    }
+    // Hash the access passphrase off the async workers (Argon2 is deliberately slow)
+    let access_passphrase_hash = match req.access_passphrase.clone() {
+        Some(passphrase) => Some(
+            tokio::task::spawn_blocking(move || access::hash_passphrase(&passphrase))
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .map_err(|e| {
+                    tracing::error!("Failed to hash access passphrase: {:?}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?,
+        ),
+        None => None,
+    };
 
     let wentu_id = Uuid::new_v4();
-    let creator_key = Uuid::new_v4().to_string();
     let creator_participant_id = Uuid::new_v4();
-    let creator_participant_key = Uuid::new_v4().to_string();
-    let slug = format!("{}-{}", slugify(&title), &creator_key[..8]);
 
-    // Wentu expires 7 days AFTER the voting deadline, not after creation
-    let expires_at = req.pref_deadline + chrono::Duration::days(7);
+    // Wentu expires a retention window AFTER the voting deadline, not after creation
+    let expires_at = req.pref_deadline + state.settings.wentu_retention();
 
-    tracing::info!("Creating wentu: {} with slug: {}", wentu_id, slug);
+    // Insert wentu, regenerating the slug if it collides with an existing one
+    let mut attempts = 0;
+    let slug = loop {
+        attempts += 1;
+        let candidate = vanity_slug
+            .clone()
+            .unwrap_or_else(|| slug::generate(state.settings.slug_strategy, &title));
 
-    // Insert wentu
-    sqlx::query(
-        "INSERT INTO wentus (id, slug, title, description, creator_name, creator_key, created_at, expires_at, pref_deadline, status, timezone)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::wentu_status, $11)"
-    )
-    .bind(wentu_id)
-    .bind(&slug)
-    .bind(&title)
-    .bind(&description)
-    .bind(&creator_name)
-    .bind(&creator_key)
-    .bind(Utc::now())
-    .bind(expires_at)
-    .bind(&req.pref_deadline)
-    .bind("open")
-    .bind(&req.timezone)
-    .execute(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to insert wentu: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+        let inserted = sqlx::query(
+            "INSERT INTO wentus (id, slug, title, description, creator_name, created_at, expires_at, pref_deadline, status, timezone, access_passphrase_hash, invite_only, anonymous, results_visibility)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9::wentu_status, $10, $11, $12, $13, $14)"
+        )
+        .bind(wentu_id)
+        .bind(&candidate)
+        .bind(&title)
+        .bind(&description)
+        .bind(&creator_name)
+        .bind(Utc::now())
+        .bind(expires_at)
+        .bind(req.pref_deadline)
+        .bind("open")
+        .bind(&req.timezone)
+        .bind(&access_passphrase_hash)
+        .bind(invite_only)
+        .bind(anonymous)
+        .bind(results_visibility)
+        .execute(&state.db)
+        .await;
+
+        match inserted {
+            Ok(_) => break candidate,
+            Err(e) if slug::is_slug_conflict(&e) => {
+                if vanity_slug.is_some() {
+                    tracing::info!("Vanity slug {} already taken", candidate);
+                    return Err(StatusCode::CONFLICT);
+                }
+                if attempts >= slug::MAX_SLUG_ATTEMPTS {
+                    tracing::error!(
+                        "Gave up generating a unique slug after {} attempts",
+                        attempts
+                    );
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+                tracing::warn!("Slug collision on {}, retrying", candidate);
+            }
+            Err(e) => {
+                tracing::error!("Failed to insert wentu: {:?}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+    };
+
+    tracing::info!("Creating wentu: {} with slug: {}", wentu_id, slug);
 
     // Expand date range into individual days/time slots and insert as date options
     if req.enable_time_slots.unwrap_or(false) {
@@ -147,7 +221,7 @@ pub async fn create_wentu(
 
             // Get slots for this day (validation: must exist and not be empty)
             let slots = time_slots.get(&date_key).ok_or(StatusCode::BAD_REQUEST)?;
-            if slots.is_empty() || slots.len() > 3 {
+            if slots.is_empty() || slots.len() > state.settings.max_slots_per_day {
                 return Err(StatusCode::BAD_REQUEST);
             }
 
@@ -229,7 +303,7 @@ pub async fn create_wentu(
             .bind(current_date)
             .bind(next_day)
             .bind(label)
-            .bind(day_count as i32)
+            .bind(day_count)
             .execute(&state.db)
             .await
             .map_err(|e| {
@@ -245,16 +319,15 @@ pub async fn create_wentu(
     tracing::info!("Successfully created wentu: {}", wentu_id);
 
     // Automatically create a participant entry for the creator so they can vote immediately
-    let creator_token_expires = Utc::now() + Duration::days(7);
+    let creator_token_expires = Utc::now() + state.settings.token_ttl();
 
     sqlx::query(
-        "INSERT INTO participants (id, wentu_id, name, participant_key, is_creator, joined_at, token_expires_at)
-         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        "INSERT INTO participants (id, wentu_id, name, is_creator, joined_at, token_expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6)",
     )
     .bind(creator_participant_id)
     .bind(wentu_id)
-    .bind(&req.creator_name)
-    .bind(&creator_participant_key)
+    .bind(&creator_name)
     .bind(true)
     .bind(Utc::now())
     .bind(creator_token_expires)
@@ -265,26 +338,45 @@ pub async fn create_wentu(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
+    let invites = invite::insert_invites(&state.db, wentu_id, &slug, &invitees).await?;
+
     audit::log_action(
         &state.db,
         "CREATE_WENTU",
         "wentu",
         Some(wentu_id),
-        Some(&creator_key),
-        Some(json!({ "slug": slug.clone(), "title": title })),
+        Some(audit::Actor::participant(creator_participant_id)),
+        Some(json!({
+            "slug": slug.clone(),
+            "title": title,
+            "passphrase_protected": access_passphrase_hash.is_some(),
+            "invite_only": invite_only,
+            "invites": invites.len(),
+            "anonymous": anonymous,
+            "results_visibility": results_visibility,
+        })),
         true,
     )
     .await;
 
+    let creator_participant_token = state.tokens.issue(&TokenClaims::new(
+        wentu_id,
+        creator_participant_id,
+        Role::Creator,
+        creator_token_expires,
+        0,
+    ));
+
     Ok((
         StatusCode::CREATED,
         Json(CreateWentuResponse {
             id: wentu_id,
             slug: slug.clone(),
             link: format!("/wentu/{}", slug),
-            creator_key,
             creator_participant_id,
-            creator_participant_key,
+            creator_participant_token,
+            creator_token_expires_at: creator_token_expires,
+            invites,
         }),
     ))
 }
@@ -293,12 +385,13 @@ pub async fn create_wentu(
 pub async fn get_wentu(
     State(state): State<AppState>,
     Path(slug): Path<String>,
+    headers: HeaderMap,
 ) -> Result<Json<Wentu>, StatusCode> {
     tracing::info!("GET wentu: {}", slug);
 
     // Fetch wentu from database
     let wentu_result = sqlx::query_as::<_, WentuRow>(
-        "SELECT id, slug, title, description, creator_name, creator_key, created_at, expires_at, pref_deadline, status::text, timezone
+        "SELECT id, slug, title, description, creator_name, created_at, expires_at, pref_deadline, status::text, timezone, access_passphrase_hash, invite_only, anonymous, results_visibility
          FROM wentus WHERE slug = $1"
     )
     .bind(&slug)
@@ -310,6 +403,15 @@ pub async fn get_wentu(
     })?
     .ok_or(StatusCode::NOT_FOUND)?;
 
+    access::require_access(
+        &state,
+        wentu_result.id,
+        &slug,
+        wentu_result.access_passphrase_hash.as_deref(),
+        &headers,
+    )
+    .await?;
+
     // Fetch date options
     let date_rows = sqlx::query_as::<_, DateRangeRow>(
         "SELECT id, start_time, end_time, label FROM date_ranges WHERE wentu_id = $1 ORDER BY sort_order"
@@ -334,6 +436,14 @@ pub async fn get_wentu(
         status = WentuStatus::Expired;
     }
 
+    let results_policy = ResultsPolicy {
+        visibility: wentu_result.results_visibility,
+        closed: !matches!(status, WentuStatus::Open),
+        pref_deadline: wentu_result.pref_deadline,
+    };
+    let results_available =
+        access::can_view_results(&state, wentu_result.id, results_policy, &headers).await?;
+
     let date_options = date_rows
         .into_iter()
         .map(|row| DateRange {
@@ -350,50 +460,55 @@ pub async fn get_wentu(
         title: wentu_result.title,
         description: wentu_result.description,
         creator_name: wentu_result.creator_name,
-        creator_key: wentu_result.creator_key,
         created_at: wentu_result.created_at,
         expires_at: wentu_result.expires_at,
         pref_deadline: wentu_result.pref_deadline,
         status,
         date_options,
         timezone: wentu_result.timezone,
+        requires_passphrase: wentu_result.access_passphrase_hash.is_some(),
+        invite_only: wentu_result.invite_only,
+        anonymous: wentu_result.anonymous,
+        results_visibility: wentu_result.results_visibility,
+        results_available,
+        results_available_at: results_policy.public_at(Utc::now()),
     };
 
     Ok(Json(wentu))
 }
 
-/// Close wentu early
+/// Close wentu early (creator token)
 pub async fn close_wentu(
     State(state): State<AppState>,
     Path(slug): Path<String>,
-    Json(req): Json<CloseWentuRequest>,
+    auth: AuthParticipant,
 ) -> Result<StatusCode, StatusCode> {
-    req.validate().map_err(|e| {
-        tracing::warn!("close_wentu validation failed: {:?}", e);
-        StatusCode::BAD_REQUEST
-    })?;
+    auth.authorize_slug(&state.db, &slug).await?;
 
-    let creator_key_trimmed = req.creator_key.trim();
-    let creator_key = Uuid::parse_str(creator_key_trimmed)
-        .map_err(|_| {
-            tracing::warn!("close_wentu invalid key format for slug {}", slug);
-            StatusCode::BAD_REQUEST
-        })?
-        .to_string();
-
-    let wentu_row = sqlx::query("SELECT id, status::text, creator_key FROM wentus WHERE slug = $1")
-        .bind(&slug)
-        .fetch_optional(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to fetch wentu {}: {:?}", slug, e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    if !auth.is_creator() {
+        tracing::warn!("close_wentu by non-creator on slug {}", slug);
+        audit::log_action(
+            &state.db,
+            "CLOSE_WENTU",
+            "wentu",
+            Some(auth.wentu_id),
+            Some(audit::Actor::participant(auth.participant_id)),
+            Some(json!({ "slug": slug, "reason": "not_creator" })),
+            false,
+        )
+        .await;
+        return Err(StatusCode::FORBIDDEN);
+    }
 
-    let wentu_id: Uuid = wentu_row.get(0);
-    let current_status: String = wentu_row.get(1);
-    let stored_creator_key: String = wentu_row.get(2);
+    let current_status =
+        sqlx::query_scalar::<_, String>("SELECT status::text FROM wentus WHERE id = $1")
+            .bind(auth.wentu_id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to fetch wentu {}: {:?}", slug, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
 
     match current_status.as_str() {
         "closed" => {
@@ -407,23 +522,8 @@ pub async fn close_wentu(
         _ => {}
     }
 
-    if stored_creator_key != creator_key {
-        tracing::warn!("close_wentu unauthorized for slug {}", slug);
-        audit::log_action(
-            &state.db,
-            "CLOSE_WENTU",
-            "wentu",
-            Some(wentu_id),
-            Some(creator_key_trimmed),
-            Some(json!({ "slug": slug.clone(), "reason": "unauthorized" })),
-            false,
-        )
-        .await;
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-
     sqlx::query("UPDATE wentus SET status = 'closed'::wentu_status WHERE id = $1")
-        .bind(wentu_id)
+        .bind(auth.wentu_id)
         .execute(&state.db)
         .await
         .map_err(|e| {
@@ -436,22 +536,11 @@ pub async fn close_wentu(
         &state.db,
         "CLOSE_WENTU",
         "wentu",
-        Some(wentu_id),
-        Some(creator_key_trimmed),
+        Some(auth.wentu_id),
+        Some(audit::Actor::participant(auth.participant_id)),
         Some(json!({ "slug": slug })),
         true,
     )
     .await;
     Ok(StatusCode::OK)
 }
-
-fn slugify(s: &str) -> String {
-    s.to_lowercase()
-        .chars()
-        .map(|c| if c.is_alphanumeric() { c } else { '-' })
-        .collect::<String>()
-        .split('-')
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<_>>()
-        .join("-")
-}
@@ -0,0 +1,184 @@
+use axum::{extract::State, http::StatusCode, Json};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::db;
+use crate::handlers::AppState;
+
+/// Longest a readiness DB round-trip may take before the check fails.
+const DB_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Slack added on top of a task's interval before its heartbeat counts as stale.
+const HEARTBEAT_GRACE: Duration = Duration::from_secs(30);
+
+struct Heartbeat {
+    interval: Duration,
+    registered_at: Instant,
+    last_beat: Option<Instant>,
+}
+
+/// Last-seen times for background tasks, so readiness can tell when one has died or hung.
+#[derive(Clone, Default)]
+pub struct Heartbeats {
+    tasks: Arc<Mutex<HashMap<&'static str, Heartbeat>>>,
+}
+
+impl Heartbeats {
+    /// Register a task that should beat at least once per `interval`.
+    pub fn register(&self, task: &'static str, interval: Duration) {
+        self.tasks.lock().unwrap().insert(
+            task,
+            Heartbeat {
+                interval,
+                registered_at: Instant::now(),
+                last_beat: None,
+            },
+        );
+    }
+
+    pub fn beat(&self, task: &'static str) {
+        if let Some(heartbeat) = self.tasks.lock().unwrap().get_mut(task) {
+            heartbeat.last_beat = Some(Instant::now());
+        }
+    }
+
+    /// Per-task breakdown plus whether every task is fresh. A task counts as stale when it
+    /// has not beaten (or, before its first beat, been registered) within twice its interval
+    /// plus a grace period.
+    fn check(&self, now: Instant) -> (bool, Value) {
+        let tasks = self.tasks.lock().unwrap();
+        let mut all_ok = true;
+        let mut breakdown = Map::new();
+
+        for (task, heartbeat) in tasks.iter() {
+            let deadline = heartbeat.interval * 2 + HEARTBEAT_GRACE;
+            let since = heartbeat.last_beat.unwrap_or(heartbeat.registered_at);
+            let ok = now.saturating_duration_since(since) <= deadline;
+            all_ok &= ok;
+            breakdown.insert(
+                task.to_string(),
+                json!({
+                    "ok": ok,
+                    "last_beat_secs_ago": heartbeat
+                        .last_beat
+                        .map(|beat| now.saturating_duration_since(beat).as_secs()),
+                    "interval_secs": heartbeat.interval.as_secs(),
+                }),
+            );
+        }
+
+        (all_ok, Value::Object(breakdown))
+    }
+}
+
+/// Liveness: the process is up and serving requests. Deliberately does not touch the
+/// database, so a Postgres outage does not get the server restarted.
+pub async fn live() -> &'static str {
+    "OK"
+}
+
+/// Readiness: a pooled DB round-trip succeeds, all migrations this build knows about are
+/// applied, background tasks are beating, and the server is not shutting down. Returns 503
+/// with the breakdown otherwise.
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let started = Instant::now();
+    let database = tokio::time::timeout(
+        DB_CHECK_TIMEOUT,
+        sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(&state.db),
+    )
+    .await;
+    let database_ok = matches!(database, Ok(Ok(1)));
+    let database_check = json!({
+        "ok": database_ok,
+        "latency_ms": started.elapsed().as_millis() as u64,
+        // Details go to the log only; the probe endpoint is unauthenticated
+        "error": match &database {
+            Ok(Ok(_)) => None,
+            Ok(Err(e)) => {
+                tracing::warn!("Readiness: database check failed: {:?}", e);
+                Some("query failed")
+            }
+            Err(_) => Some("timed out"),
+        },
+    });
+
+    let expected = db::latest_migration_version();
+    let applied = if database_ok {
+        db::applied_migration_version(&state.db)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Readiness: failed to read migration version: {:?}", e);
+                None
+            })
+    } else {
+        None
+    };
+    let migrations_ok = applied.is_some() && applied >= expected;
+
+    let (tasks_ok, tasks_check) = state.heartbeats.check(Instant::now());
+
+    // Draining: tell load balancers to stop sending new traffic
+    let shutting_down = state.shutdown.is_triggered();
+
+    let ready = database_ok && migrations_ok && tasks_ok && !shutting_down;
+    if !ready && !shutting_down {
+        tracing::warn!(
+            "Readiness check failed (database={} migrations={} background_tasks={})",
+            database_ok,
+            migrations_ok,
+            tasks_ok
+        );
+    }
+
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": match (ready, shutting_down) {
+                (true, _) => "ok",
+                (false, true) => "shutting_down",
+                (false, false) => "unavailable",
+            },
+            "checks": {
+                "database": database_check,
+                "migrations": {
+                    "ok": migrations_ok,
+                    "applied": applied,
+                    "expected": expected,
+                },
+                "background_tasks": tasks_check,
+            },
+        })),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_staleness() {
+        let heartbeats = Heartbeats::default();
+        heartbeats.register("reaper", Duration::from_secs(60));
+
+        let later = Instant::now() + Duration::from_secs(60 * 2) + HEARTBEAT_GRACE * 2;
+
+        // Not beaten yet, but only just registered
+        assert!(heartbeats.check(Instant::now()).0);
+        assert!(!heartbeats.check(later).0);
+
+        heartbeats.beat("reaper");
+        let (ok, breakdown) = heartbeats.check(Instant::now());
+        assert!(ok);
+        assert_eq!(breakdown["reaper"]["ok"], true);
+
+        assert!(!heartbeats.check(later).0);
+    }
+}
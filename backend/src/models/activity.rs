@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Filters and cursor for the creator's activity feed.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ActivityQuery {
+    #[validate(range(min = 1, max = 100, message = "Limit must be 1-100"))]
+    pub limit: Option<i64>,
+    /// `next_cursor` from the previous page
+    pub before: Option<Uuid>,
+    /// Audit action, e.g. `JOIN_WENTU`
+    #[validate(length(min = 1, max = 100, message = "Action must be 1-100 characters"))]
+    pub action: Option<String>,
+    /// Entries where this participant acted or was acted on
+    pub participant_id: Option<Uuid>,
+    pub success: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActivityParticipant {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActivityEntry {
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub action: String,
+    pub success: bool,
+    /// Who did it; `None` for anonymous or failed attempts
+    pub actor: Option<ActivityParticipant>,
+    /// Participant the action applied to, for participant-level entries
+    pub subject: Option<ActivityParticipant>,
+    pub details: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActivityPage {
+    pub entries: Vec<ActivityEntry>,
+    /// Pass as `before` to fetch the next (older) page; `None` on the last page
+    pub next_cursor: Option<Uuid>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct BallotVerification {
+    /// Participants whose stored ballot was compared against a recorded digest
+    pub checked: usize,
+    /// Stored ballots that differ from (or are missing for) the last recorded digest
+    pub mismatched: usize,
+    /// Stored ballots with no digest in the chain that were not there when chaining began
+    pub unrecorded: usize,
+    /// Stored ballots cast before chaining existed, which have no digest to check against;
+    /// reported but not counted against `valid`
+    pub legacy: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditVerification {
+    /// Chain intact and every stored ballot, other than legacy ones, matches its recorded digest
+    pub valid: bool,
+    pub entries: usize,
+    /// Hash of the newest entry; record it to detect the chain being rewritten later
+    pub head_hash: Option<String>,
+    /// First chain position that failed verification
+    pub broken_at: Option<i64>,
+    pub reason: Option<&'static str>,
+    /// False for anonymous wentus, whose ballots have no digests in the chain; `valid` is
+    /// then false too, and `broken_at` tells whether the chain itself is intact
+    pub ballots_verifiable: bool,
+    pub ballots: BallotVerification,
+}
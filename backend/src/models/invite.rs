@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateInvitesRequest {
+    #[validate(length(min = 1, max = 200, message = "Provide 1-200 invitee names"))]
+    pub names: Vec<String>,
+}
+
+/// A freshly minted invite; the token is only ever shown once.
+#[derive(Debug, Serialize)]
+pub struct InviteLink {
+    pub id: Uuid,
+    pub name: String,
+    pub invite_token: String,
+    pub link: String,
+}
@@ -1,9 +1,16 @@
+pub mod activity;
+pub mod invite;
 pub mod participant;
 pub mod ranking;
 pub mod wentu;
 
-pub use participant::{JoinWentuRequest, JoinWentuResponse, Participant};
-pub use ranking::{Ranking, UpdatePreferencesRequest};
+pub use activity::{
+    ActivityEntry, ActivityPage, ActivityParticipant, ActivityQuery, AuditVerification,
+    BallotVerification,
+};
+pub use invite::{CreateInvitesRequest, InviteLink};
+pub use participant::{JoinWentuRequest, JoinWentuResponse, TokenResponse};
+pub use ranking::{BallotHistory, BallotReceipt, BallotVersion, UpdatePreferencesRequest};
 pub use wentu::{
-    CloseWentuRequest, CreateWentuRequest, CreateWentuResponse, DateRange, Wentu, WentuStatus,
+    CreateWentuRequest, CreateWentuResponse, DateRange, ResultsVisibility, Wentu, WentuStatus,
 };
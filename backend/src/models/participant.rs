@@ -3,12 +3,12 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Participant {
     pub id: Uuid,
     pub wentu_id: Uuid,
     pub name: String,
-    pub participant_key: String,
     pub is_creator: bool,
     pub joined_at: DateTime<Utc>,
     pub token_expires_at: DateTime<Utc>,
@@ -16,12 +16,24 @@ pub struct Participant {
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct JoinWentuRequest {
+    /// Required unless joining through an invite, which carries the invitee's name
     #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
-    pub name: String,
+    pub name: Option<String>,
+    /// Required for invite-only wentus
+    #[validate(length(min = 1, max = 255, message = "Invite token must be 1-255 characters"))]
+    pub invite_token: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct JoinWentuResponse {
     pub participant_id: Uuid,
-    pub participant_key: String,
+    pub token: String,
+    pub token_expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub participant_id: Uuid,
+    pub token: String,
+    pub token_expires_at: DateTime<Utc>,
 }
@@ -1,7 +1,9 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ranking {
     pub participant_id: Uuid,
@@ -11,9 +13,6 @@ pub struct Ranking {
 
 #[derive(Debug, Deserialize, Serialize, Validate)]
 pub struct UpdatePreferencesRequest {
-    pub participant_id: Uuid,
-    #[validate(length(min = 1, max = 255, message = "Participant key required"))]
-    pub participant_key: String,
     #[validate(length(min = 1, max = 200, message = "Provide 1-200 rankings"))]
     #[validate(nested)]
     pub rankings: Vec<CreateRanking>,
@@ -25,3 +24,32 @@ pub struct CreateRanking {
     #[validate(range(min = 1, max = 200, message = "Preference order must be 1-200"))]
     pub preference_order: i32,
 }
+
+/// Proof of what was stored. `ballot_hash` appears in the published ballot export as long as
+/// this is the participant's current ballot.
+#[derive(Debug, Serialize)]
+pub struct BallotReceipt {
+    pub ballot_id: String,
+    pub ballot_hash: String,
+    pub version: i32,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// One submitted version of a participant's ballot.
+#[derive(Debug, Serialize)]
+pub struct BallotVersion {
+    pub version: i32,
+    /// The `ballot_id` the receipt for this version showed
+    pub ballot_id: String,
+    pub recorded_at: DateTime<Utc>,
+    /// Date option ids, most preferred first
+    pub ranking: Vec<Uuid>,
+    /// The `ballot_hash` the receipt for this version showed
+    pub ballot_hash: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BallotHistory {
+    /// Oldest first; empty for anonymous wentus, whose ballots are not kept per participant
+    pub versions: Vec<BallotVersion>,
+}
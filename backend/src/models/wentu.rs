@@ -1,11 +1,11 @@
 use chrono::{DateTime, Utc};
-use lazy_static::lazy_static;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 use validator::Validate;
 
+use super::InviteLink;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "wentu_status", rename_all = "lowercase")]
 pub enum WentuStatus {
@@ -17,6 +17,20 @@ pub enum WentuStatus {
     Expired,
 }
 
+/// Who may see a wentu's results, and when. The creator can always see them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "results_visibility", rename_all = "snake_case")]
+pub enum ResultsVisibility {
+    #[default]
+    Always,
+    /// Each participant once they have submitted a ballot
+    AfterVoting,
+    /// Everyone once the preference deadline passes or the wentu is closed
+    AfterDeadline,
+    CreatorOnly,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DateRange {
     pub id: Uuid,
@@ -32,13 +46,20 @@ pub struct Wentu {
     pub title: String,
     pub description: Option<String>,
     pub creator_name: String,
-    pub creator_key: String,
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
     pub pref_deadline: DateTime<Utc>,
     pub status: WentuStatus,
     pub date_options: Vec<DateRange>,
     pub timezone: Option<String>,
+    pub requires_passphrase: bool,
+    pub invite_only: bool,
+    pub anonymous: bool,
+    pub results_visibility: ResultsVisibility,
+    /// Whether the caller can see the results right now
+    pub results_available: bool,
+    /// When the results open to everyone, if that is still scheduled
+    pub results_available_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -52,6 +73,10 @@ pub struct CreateWentuRequest {
     #[validate(length(min = 1, max = 100, message = "Creator name must be 1-100 characters"))]
     pub creator_name: String,
 
+    // Optional vanity slug; otherwise one is generated
+    #[validate(length(min = 3, max = 64, message = "Slug must be 3-64 characters"))]
+    pub slug: Option<String>,
+
     pub date_range_start: DateTime<Utc>,
     pub date_range_end: DateTime<Utc>,
     pub pref_deadline: DateTime<Utc>,
@@ -60,6 +85,25 @@ pub struct CreateWentuRequest {
     pub enable_time_slots: Option<bool>,
     pub timezone: Option<String>,
     pub day_time_slots: Option<HashMap<String, Vec<String>>>,
+
+    // Optional passphrase or PIN required to view, join and see results
+    #[validate(length(
+        min = 4,
+        max = 128,
+        message = "Access passphrase must be 4-128 characters"
+    ))]
+    pub access_passphrase: Option<String>,
+
+    // Invite-only mode: only invitees may join, each through a single-use link
+    pub invite_only: Option<bool>,
+    #[validate(length(max = 200, message = "At most 200 invitees"))]
+    pub invitees: Option<Vec<String>>,
+
+    // Anonymous mode: ballots are stored without the voter and are final once cast
+    pub anonymous: Option<bool>,
+
+    // Who may see the results, and when (defaults to always)
+    pub results_visibility: Option<ResultsVisibility>,
 }
 
 #[derive(Debug, Serialize)]
@@ -67,18 +111,8 @@ pub struct CreateWentuResponse {
     pub id: Uuid,
     pub slug: String,
     pub link: String,
-    pub creator_key: String,
     pub creator_participant_id: Uuid,
-    pub creator_participant_key: String,
-}
-
-#[derive(Debug, Deserialize, Validate)]
-pub struct CloseWentuRequest {
-    #[validate(length(min = 32, max = 64, message = "creator_key must be provided"))]
-    pub creator_key: String,
-}
-
-lazy_static! {
-    pub static ref SAFE_TITLE_REGEX: Regex = Regex::new("^[a-zA-Z0-9\\s\\-_'\".,!?()]+$").unwrap();
-    pub static ref SAFE_NAME_REGEX: Regex = Regex::new("^[a-zA-Z0-9\\s\\-_'\".]+$").unwrap();
+    pub creator_participant_token: String,
+    pub creator_token_expires_at: DateTime<Utc>,
+    pub invites: Vec<InviteLink>,
 }
@@ -0,0 +1,309 @@
+use axum::{
+    extract::{Path, Request, State},
+    http::{header::RETRY_AFTER, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use governor::{
+    clock::{Clock, DefaultClock},
+    DefaultKeyedRateLimiter, Quota,
+};
+use std::collections::{HashMap, VecDeque};
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::auth::bearer_token;
+use crate::handlers::AppState;
+use crate::health::Heartbeats;
+use crate::settings::Settings;
+use crate::shutdown::Shutdown;
+use crate::telemetry;
+
+/// Header set by `tower_governor` on IP-limited responses.
+const RATE_LIMIT_AFTER: HeaderName = HeaderName::from_static("x-ratelimit-after");
+
+/// How often idle keys are dropped from the keyed limiters.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Rate limiters keyed on something other than the client IP, so one wentu or one
+/// participant cannot be flooded from many addresses and a shared NAT does not share a bucket.
+#[derive(Clone)]
+pub struct KeyedLimits {
+    /// Joins per wentu slug
+    join: Arc<DefaultKeyedRateLimiter<String>>,
+    /// Preference updates per participant
+    preferences: Arc<DefaultKeyedRateLimiter<String>>,
+    /// Close attempts per wentu slug
+    close: Arc<DefaultKeyedRateLimiter<String>>,
+    /// Wrong access passphrases per client IP and wentu
+    pub passphrase: PassphraseFailures,
+}
+
+fn quota(period_ms: u64, burst: u32) -> Quota {
+    Quota::with_period(Duration::from_millis(period_ms))
+        .expect("rate limit period is validated as positive")
+        .allow_burst(NonZeroU32::new(burst).expect("rate limit burst is validated as positive"))
+}
+
+impl KeyedLimits {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            join: Arc::new(DefaultKeyedRateLimiter::keyed(quota(
+                settings.join_rate_limit_period_ms,
+                settings.join_rate_limit_burst,
+            ))),
+            preferences: Arc::new(DefaultKeyedRateLimiter::keyed(quota(
+                settings.preferences_rate_limit_period_ms,
+                settings.preferences_rate_limit_burst,
+            ))),
+            close: Arc::new(DefaultKeyedRateLimiter::keyed(quota(
+                settings.close_rate_limit_period_ms,
+                settings.close_rate_limit_burst,
+            ))),
+            passphrase: PassphraseFailures::new(
+                settings.passphrase_max_failed_attempts as usize,
+                settings.passphrase_attempt_window(),
+            ),
+        }
+    }
+
+    /// Periodically forget keys whose buckets have refilled, until shutdown.
+    pub fn spawn_cleanup(&self, heartbeats: Heartbeats, shutdown: Shutdown) -> JoinHandle<()> {
+        heartbeats.register("rate_limit_cleanup", CLEANUP_INTERVAL);
+        let limits = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = shutdown.wait() => break,
+                }
+                for limiter in [&limits.join, &limits.preferences, &limits.close] {
+                    limiter.retain_recent();
+                    limiter.shrink_to_fit();
+                }
+                limits.passphrase.forget_expired(Instant::now());
+                heartbeats.beat("rate_limit_cleanup");
+            }
+        })
+    }
+}
+
+/// Client IP and wentu id.
+type ClientWentu = (String, Uuid);
+
+/// Wrong passphrases per client and wentu within a sliding window. Counted in memory rather
+/// than from the audit log, so a failed audit write cannot lift the limit, and keyed on the
+/// client so one client's guesses do not lock everyone else out of the wentu. An attempt
+/// counts from the moment it starts and is given back only once the guess proves right.
+#[derive(Clone)]
+pub struct PassphraseFailures {
+    max_failures: usize,
+    window: Duration,
+    failures: Arc<Mutex<HashMap<ClientWentu, VecDeque<Instant>>>>,
+}
+
+impl PassphraseFailures {
+    pub fn new(max_failures: usize, window: Duration) -> Self {
+        Self {
+            max_failures,
+            window,
+            failures: Arc::default(),
+        }
+    }
+
+    /// Count an attempt by `client` on the wentu before checking it, so parallel guesses
+    /// cannot all get past the limit while the first ones are still being verified. Returns
+    /// `None` once the client has used up its attempts; pass the returned instant to
+    /// `release` if the guess turns out right.
+    pub fn reserve(&self, client: &str, wentu_id: Uuid) -> Option<Instant> {
+        self.reserve_at(client, wentu_id, Instant::now())
+    }
+
+    /// Give back an attempt taken by `reserve`, for a correct guess.
+    pub fn release(&self, client: &str, wentu_id: Uuid, attempt: Instant) {
+        let mut failures = self
+            .failures
+            .lock()
+            .expect("passphrase failures lock poisoned");
+        if let Some(times) = failures.get_mut(&(client.to_string(), wentu_id)) {
+            if let Some(index) = times.iter().position(|&at| at == attempt) {
+                times.remove(index);
+            }
+        }
+    }
+
+    fn reserve_at(&self, client: &str, wentu_id: Uuid, now: Instant) -> Option<Instant> {
+        let mut failures = self
+            .failures
+            .lock()
+            .expect("passphrase failures lock poisoned");
+        let times = failures.entry((client.to_string(), wentu_id)).or_default();
+        times.retain(|&at| now.duration_since(at) < self.window);
+        if times.len() >= self.max_failures {
+            telemetry::record_rate_limited("passphrase");
+            return None;
+        }
+        times.push_back(now);
+        Some(now)
+    }
+
+    /// Drop clients whose failures have all left the window.
+    fn forget_expired(&self, now: Instant) {
+        let mut failures = self
+            .failures
+            .lock()
+            .expect("passphrase failures lock poisoned");
+        failures.retain(|_, times| {
+            times.retain(|&at| now.duration_since(at) < self.window);
+            !times.is_empty()
+        });
+        failures.shrink_to_fit();
+    }
+}
+
+/// Check `key` against `limiter`, returning how long to wait when exhausted.
+fn check(
+    limiter: &DefaultKeyedRateLimiter<String>,
+    key: String,
+    kind: &'static str,
+) -> Result<(), Duration> {
+    limiter.check_key(&key).map_err(|not_until| {
+        let wait = not_until.wait_time_from(DefaultClock::default().now());
+        tracing::warn!("Rate limited {} for {:?}", kind, wait);
+        telemetry::record_rate_limited(kind);
+        wait
+    })
+}
+
+/// 429 with a `Retry-After` in whole seconds.
+fn too_many_requests(wait: Duration) -> Response {
+    // Round up so clients never retry before the bucket has refilled
+    let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(RETRY_AFTER, HeaderValue::from(secs.max(1)))],
+    )
+        .into_response()
+}
+
+/// Limit joins per wentu, regardless of client IP.
+pub async fn limit_join(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Err(wait) = check(&state.limits.join, slug, "join") {
+        return too_many_requests(wait);
+    }
+    next.run(req).await
+}
+
+/// Limit preference updates per participant. Requests without a valid token are passed
+/// through; the handler rejects them.
+pub async fn limit_preferences(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let participant = bearer_token(req.headers())
+        .and_then(|token| state.tokens.verify(token).ok())
+        .map(|claims| claims.participant_id.to_string());
+
+    if let Some(participant) = participant {
+        if let Err(wait) = check(&state.limits.preferences, participant, "preferences") {
+            return too_many_requests(wait);
+        }
+    }
+    next.run(req).await
+}
+
+/// Limit close attempts per wentu, so one wentu cannot be hammered with close attempts
+/// from many IPs.
+pub async fn limit_close(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Err(wait) = check(&state.limits.close, slug, "close") {
+        return too_many_requests(wait);
+    }
+    next.run(req).await
+}
+
+/// Mirror `tower_governor`'s `x-ratelimit-after` into the standard `Retry-After` header.
+pub async fn add_retry_after(mut response: Response) -> Response {
+    if response.status() == StatusCode::TOO_MANY_REQUESTS
+        && !response.headers().contains_key(RETRY_AFTER)
+    {
+        if let Some(after) = response.headers().get(RATE_LIMIT_AFTER).cloned() {
+            telemetry::record_rate_limited("ip");
+            response.headers_mut().insert(RETRY_AFTER, after);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_independent() {
+        let limiter = DefaultKeyedRateLimiter::keyed(quota(60_000, 2));
+
+        assert!(check(&limiter, "a".into(), "test").is_ok());
+        assert!(check(&limiter, "a".into(), "test").is_ok());
+        let wait = check(&limiter, "a".into(), "test").unwrap_err();
+        assert!(check(&limiter, "b".into(), "test").is_ok());
+
+        let limited = too_many_requests(wait);
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = limited.headers()[RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=60).contains(&retry_after));
+    }
+
+    #[test]
+    fn test_passphrase_failures_per_client_and_wentu() {
+        let failures = PassphraseFailures::new(2, Duration::from_secs(60));
+        let (wentu, other_wentu) = (Uuid::new_v4(), Uuid::new_v4());
+        let start = Instant::now();
+
+        // Attempts count as soon as they start, so concurrent guesses share the limit
+        assert!(failures.reserve_at("198.51.100.7", wentu, start).is_some());
+        assert!(failures.reserve_at("198.51.100.7", wentu, start).is_some());
+        assert!(failures.reserve_at("198.51.100.7", wentu, start).is_none());
+
+        // Other clients and other wentus keep their own counts
+        assert!(failures.reserve_at("203.0.113.9", wentu, start).is_some());
+        assert!(failures
+            .reserve_at("198.51.100.7", other_wentu, start)
+            .is_some());
+
+        let later = start + Duration::from_secs(61);
+        assert!(failures.reserve_at("198.51.100.7", wentu, later).is_some());
+        failures.forget_expired(later + Duration::from_secs(61));
+        assert!(failures.failures.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_passphrase_correct_guess_gives_attempt_back() {
+        let failures = PassphraseFailures::new(1, Duration::from_secs(60));
+        let wentu = Uuid::new_v4();
+        let start = Instant::now();
+
+        let attempt = failures.reserve_at("198.51.100.7", wentu, start).unwrap();
+        failures.release("198.51.100.7", wentu, attempt);
+        assert!(failures.reserve_at("198.51.100.7", wentu, start).is_some());
+        assert!(failures.reserve_at("198.51.100.7", wentu, start).is_none());
+    }
+}
@@ -0,0 +1,64 @@
+use serde_json::json;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::audit;
+use crate::health::Heartbeats;
+use crate::shutdown::Shutdown;
+use crate::telemetry;
+
+const TASK_NAME: &str = "reaper";
+
+/// Periodically delete wentus whose retention window has passed (`expires_at` is the
+/// preference deadline plus `WENTU_RETENTION_DAYS`). Participants, options, rankings and
+/// invites go with them via `ON DELETE CASCADE`. Stops at the next tick after shutdown.
+pub fn spawn(
+    db: PgPool,
+    interval: Duration,
+    heartbeats: Heartbeats,
+    shutdown: Shutdown,
+) -> JoinHandle<()> {
+    heartbeats.register(TASK_NAME, interval);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.wait() => break,
+            }
+            match reap_expired(&db).await {
+                Ok(0) => heartbeats.beat(TASK_NAME),
+                Ok(count) => {
+                    tracing::info!("Reaper deleted {} expired wentus", count);
+                    heartbeats.beat(TASK_NAME);
+                }
+                // No beat: repeated failures show up as a stale reaper in readiness
+                Err(e) => tracing::error!("Reaper failed: {:?}", e),
+            }
+        }
+    })
+}
+
+async fn reap_expired(db: &PgPool) -> Result<u64, sqlx::Error> {
+    let deleted = sqlx::query("DELETE FROM wentus WHERE expires_at < NOW()")
+        .execute(db)
+        .await?
+        .rows_affected();
+
+    if deleted > 0 {
+        telemetry::record_reaped(deleted);
+        audit::log_action(
+            db,
+            "REAP_WENTUS",
+            "wentu",
+            None,
+            None,
+            Some(json!({ "deleted": deleted })),
+            true,
+        )
+        .await;
+    }
+
+    Ok(deleted)
+}
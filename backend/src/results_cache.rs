@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+use uuid::Uuid;
+
+use crate::stv::explain::Explanation;
+use crate::stv::STVResult;
+use crate::telemetry;
+
+/// The part of a wentu's results that depends only on its ballots.
+pub struct CountedResults {
+    pub result: STVResult,
+    pub explanation: Explanation,
+    pub total_voters: usize,
+}
+
+struct Entry {
+    ballots_version: i64,
+    counted: Arc<CountedResults>,
+    stored_at: Instant,
+}
+
+/// Counted results per wentu, tagged with the wentu's `ballots_version` at the time. An entry
+/// is only served for that same version, so any ballot change invalidates it. Holds at most
+/// `capacity` wentus, dropping the oldest entry when full; a capacity of 0 disables caching.
+#[derive(Clone)]
+pub struct ResultsCache {
+    entries: Arc<Mutex<HashMap<Uuid, Entry>>>,
+    capacity: usize,
+}
+
+impl ResultsCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            capacity,
+        }
+    }
+
+    pub fn get(&self, wentu_id: Uuid, ballots_version: i64) -> Option<Arc<CountedResults>> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let hit = entries
+            .get(&wentu_id)
+            .filter(|entry| entry.ballots_version == ballots_version)
+            .map(|entry| entry.counted.clone());
+        telemetry::record_results_cache(hit.is_some());
+        hit
+    }
+
+    pub fn insert(&self, wentu_id: Uuid, ballots_version: i64, counted: Arc<CountedResults>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // A slower request must not replace results counted at a newer version
+        if entries
+            .get(&wentu_id)
+            .is_some_and(|entry| entry.ballots_version > ballots_version)
+        {
+            return;
+        }
+        if entries.len() >= self.capacity && !entries.contains_key(&wentu_id) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(&id, _)| id);
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(
+            wentu_id,
+            Entry {
+                ballots_version,
+                counted,
+                stored_at: Instant::now(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stv::calculate_stv;
+    use crate::stv::explain::explain;
+
+    fn counted(total_voters: usize) -> Arc<CountedResults> {
+        let result = calculate_stv(vec![], vec![]);
+        Arc::new(CountedResults {
+            explanation: explain(&result, &[], total_voters),
+            result,
+            total_voters,
+        })
+    }
+
+    #[test]
+    fn test_served_only_for_same_version() {
+        let cache = ResultsCache::new(10);
+        let wentu_id = Uuid::new_v4();
+
+        assert!(cache.get(wentu_id, 0).is_none());
+        cache.insert(wentu_id, 3, counted(3));
+        assert_eq!(cache.get(wentu_id, 3).unwrap().total_voters, 3);
+        assert!(cache.get(wentu_id, 4).is_none());
+
+        // Results counted at an older version do not overwrite newer ones
+        cache.insert(wentu_id, 2, counted(2));
+        assert_eq!(cache.get(wentu_id, 3).unwrap().total_voters, 3);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let cache = ResultsCache::new(2);
+        let wentus: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        for &wentu_id in &wentus {
+            cache.insert(wentu_id, 1, counted(1));
+        }
+
+        assert!(cache.get(wentus[0], 1).is_none());
+        assert!(cache.get(wentus[1], 1).is_some());
+        assert!(cache.get(wentus[2], 1).is_some());
+
+        let disabled = ResultsCache::new(0);
+        disabled.insert(wentus[0], 1, counted(1));
+        assert!(disabled.get(wentus[0], 1).is_none());
+    }
+}
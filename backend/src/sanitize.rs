@@ -0,0 +1,229 @@
+use unicode_categories::UnicodeCategories;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Longest participant or creator name, in characters.
+pub const MAX_NAME_CHARS: usize = 100;
+/// Longest wentu title, in characters.
+pub const MAX_TITLE_CHARS: usize = 255;
+
+/// Punctuation accepted in names beyond dashes and connectors (apostrophes, initials, middle dots).
+const NAME_PUNCTUATION: &[char] = &['\'', '\u{2019}', '\u{2018}', '"', '.', '\u{b7}', '\u{30fb}'];
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SanitizeError {
+    #[error("value is empty")]
+    Empty,
+    #[error("value is longer than {0} characters")]
+    TooLong(usize),
+    #[error("invisible or control character U+{:04X}", *.0 as u32)]
+    Invisible(char),
+    #[error("character U+{:04X} is not allowed here", *.0 as u32)]
+    Disallowed(char),
+}
+
+/// Bidi embeddings/overrides/isolates and marks that can reorder how text is displayed.
+fn is_bidi_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// Zero-width and otherwise invisible characters.
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' | '\u{180E}' | '\u{200B}'..='\u{200D}' | '\u{2060}'..='\u{2064}' | '\u{FEFF}'
+    )
+}
+
+/// NFC-normalize, reject invisible characters, collapse whitespace runs and trim.
+fn normalize(input: &str, max_chars: usize) -> Result<String, SanitizeError> {
+    let mut out = String::with_capacity(input.len());
+    let mut pending_space = false;
+
+    for c in input.nfc() {
+        if c.is_whitespace() && !c.is_control() {
+            pending_space = true;
+            continue;
+        }
+        if c.is_control() || c.is_other_format() || is_bidi_control(c) || is_zero_width(c) {
+            return Err(SanitizeError::Invisible(c));
+        }
+        if pending_space && !out.is_empty() {
+            out.push(' ');
+        }
+        pending_space = false;
+        out.push(c);
+    }
+
+    if out.is_empty() {
+        return Err(SanitizeError::Empty);
+    }
+    if out.chars().count() > max_chars {
+        return Err(SanitizeError::TooLong(max_chars));
+    }
+    Ok(out)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphabetic() || c.is_numeric() || c.is_mark()
+}
+
+/// Sanitize a participant, creator or invitee name.
+///
+/// Letters, numbers and combining marks from any script are accepted, plus spaces, dashes,
+/// connectors and the apostrophes/dots that appear in real names.
+pub fn sanitize_name(input: &str) -> Result<String, SanitizeError> {
+    let name = normalize(input, MAX_NAME_CHARS)?;
+    match name.chars().find(|&c| {
+        !(is_word_char(c)
+            || c == ' '
+            || c.is_punctuation_dash()
+            || c.is_punctuation_connector()
+            || NAME_PUNCTUATION.contains(&c))
+    }) {
+        Some(c) => Err(SanitizeError::Disallowed(c)),
+        None => Ok(name),
+    }
+}
+
+/// Sanitize a wentu title: like names, but any punctuation is allowed.
+pub fn sanitize_title(input: &str) -> Result<String, SanitizeError> {
+    let title = normalize(input, MAX_TITLE_CHARS)?;
+    match title
+        .chars()
+        .find(|&c| !(is_word_char(c) || c == ' ' || c.is_punctuation()))
+    {
+        Some(c) => Err(SanitizeError::Disallowed(c)),
+        None => Ok(title),
+    }
+}
+
+/// Fold characters that render like a Latin letter or digit onto it.
+///
+/// Input is already lowercased, so letters whose *capital* form is the lookalike
+/// (Cyrillic "В", Greek "Η") are folded from their lowercase form.
+fn fold_confusable(c: char) -> char {
+    match c {
+        // Cyrillic
+        'а' => 'a',
+        'в' => 'b',
+        'е' => 'e',
+        'і' => 'i',
+        'ј' => 'j',
+        'к' => 'k',
+        'м' => 'm',
+        'н' => 'h',
+        'о' => 'o',
+        'р' => 'p',
+        'с' => 'c',
+        'т' => 't',
+        'у' => 'y',
+        'х' => 'x',
+        'ѕ' => 's',
+        'һ' => 'h',
+        'ԁ' => 'd',
+        'ԛ' => 'q',
+        'ԝ' => 'w',
+        'ӏ' => 'l',
+        // Greek
+        'α' => 'a',
+        'β' => 'b',
+        'ε' => 'e',
+        'ζ' => 'z',
+        'η' => 'h',
+        'ι' => 'i',
+        'κ' => 'k',
+        'μ' => 'm',
+        'ν' => 'v',
+        'ο' => 'o',
+        'ρ' => 'p',
+        'τ' => 't',
+        'υ' => 'u',
+        'χ' => 'x',
+        // Latin lookalikes and digits
+        'ı' => 'i',
+        'ɑ' => 'a',
+        'ɡ' => 'g',
+        '0' => 'o',
+        '1' => 'l',
+        '|' => 'l',
+        other => other,
+    }
+}
+
+/// Confusable skeleton in the spirit of UTS #39: two names with the same skeleton
+/// look alike to a human (e.g. "Bob" vs Cyrillic "Воb", "Zoë" vs "Zoe").
+pub fn skeleton(name: &str) -> String {
+    name.nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .map(fold_confusable)
+        .collect::<String>()
+        .replace("rn", "m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_international_names() {
+        for name in [
+            "José",
+            "Zoë",
+            "王伟",
+            "Ólafur Arnalds",
+            "O’Brien",
+            "Jean-Luc",
+            "Дмитрий",
+        ] {
+            assert_eq!(sanitize_name(name).as_deref(), Ok(name));
+        }
+    }
+
+    #[test]
+    fn test_normalizes_to_nfc_and_collapses_whitespace() {
+        // "e" + combining acute accent composes to a single "é"
+        assert_eq!(sanitize_name("  Jose\u{301}   Luis ").unwrap(), "José Luis");
+        assert_eq!(sanitize_title("Réunion\u{3000}Q1").unwrap(), "Réunion Q1");
+    }
+
+    #[test]
+    fn test_rejects_invisible_characters() {
+        assert_eq!(
+            sanitize_name("Bob\u{202E}evil"),
+            Err(SanitizeError::Invisible('\u{202E}'))
+        );
+        assert_eq!(
+            sanitize_name("Bo\u{200B}b"),
+            Err(SanitizeError::Invisible('\u{200B}'))
+        );
+        assert_eq!(
+            sanitize_title("Line\nbreak"),
+            Err(SanitizeError::Invisible('\n'))
+        );
+        assert_eq!(sanitize_name(" \u{3000} "), Err(SanitizeError::Empty));
+    }
+
+    #[test]
+    fn test_rejects_symbols() {
+        assert_eq!(sanitize_name("Bob!"), Err(SanitizeError::Disallowed('!')));
+        assert_eq!(
+            sanitize_title("Party 🎉"),
+            Err(SanitizeError::Disallowed('🎉'))
+        );
+        assert!(sanitize_title("Q1 planning: what's next? (draft)").is_ok());
+    }
+
+    #[test]
+    fn test_confusable_skeletons() {
+        assert_eq!(skeleton("Bob"), skeleton("Воb"));
+        assert_eq!(skeleton("Zoë"), skeleton("zoe"));
+        assert_eq!(skeleton("Zoë"), skeleton("Ζoë"));
+        assert_eq!(skeleton("Bill"), skeleton("Bi1l"));
+        assert_ne!(skeleton("Bob"), skeleton("Rob"));
+    }
+}
@@ -0,0 +1,132 @@
+use std::future::IntoFuture;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Process-wide shutdown flag shared by the servers, background tasks and readiness check.
+#[derive(Clone)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<bool>>,
+    rx: watch::Receiver<bool>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        let (tx, rx) = watch::channel(false);
+        Self {
+            tx: Arc::new(tx),
+            rx,
+        }
+    }
+}
+
+impl Shutdown {
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    /// Resolve once shutdown has been triggered.
+    pub async fn wait(&self) {
+        let mut rx = self.rx.clone();
+        // The sender lives as long as any `Shutdown`, including `self`, so this cannot fail
+        let _ = rx.wait_for(|triggered| *triggered).await;
+    }
+
+    /// Trigger shutdown on SIGINT or SIGTERM.
+    pub fn listen_for_signals(&self) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            signal().await;
+            tracing::info!("Shutdown signal received, draining connections");
+            shutdown.trigger();
+        });
+    }
+}
+
+async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {:?}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {:?}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Run `server` until it finishes, giving it at most `timeout` to drain once shutdown has
+/// been triggered.
+pub async fn drain<E>(
+    shutdown: &Shutdown,
+    timeout: Duration,
+    server: impl IntoFuture<Output = Result<(), E>>,
+) -> Result<(), E> {
+    let deadline = async {
+        shutdown.wait().await;
+        tokio::time::sleep(timeout).await;
+    };
+
+    tokio::select! {
+        result = server.into_future() => result,
+        _ = deadline => {
+            tracing::warn!("Connections still open after {:?}, shutting down anyway", timeout);
+            Ok(())
+        }
+    }
+}
+
+/// Wait for background tasks to notice shutdown and finish their current iteration.
+pub async fn join_tasks(tasks: Vec<JoinHandle<()>>, timeout: Duration) {
+    let all = async {
+        for task in tasks {
+            if let Err(e) = task.await {
+                tracing::error!("Background task failed: {:?}", e);
+            }
+        }
+    };
+    if tokio::time::timeout(timeout, all).await.is_err() {
+        tracing::warn!("Background tasks did not stop within {:?}", timeout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_times_out_after_trigger() {
+        let shutdown = Shutdown::default();
+        let stuck_server = std::future::pending::<Result<(), ()>>();
+
+        shutdown.trigger();
+        let started = std::time::Instant::now();
+        let result = drain(&shutdown, Duration::from_millis(50), stuck_server).await;
+
+        assert!(result.is_ok());
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(shutdown.is_triggered());
+    }
+}
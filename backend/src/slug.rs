@@ -0,0 +1,174 @@
+use lazy_static::lazy_static;
+use rand::RngCore;
+use regex::Regex;
+use serde::Deserialize;
+use std::str::FromStr;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// How many times `create_wentu` regenerates a slug after a unique-constraint collision.
+pub const MAX_SLUG_ATTEMPTS: usize = 5;
+
+/// Random bytes behind each generated slug (80 bits, 16 base32 characters).
+const RANDOM_BYTES: usize = 10;
+/// Longest title-derived prefix kept by the `prefixed` strategy.
+const MAX_PREFIX_LEN: usize = 24;
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Slugs that would shadow application routes or look official.
+const RESERVED_SLUGS: &[&str] = &[
+    "admin", "api", "assets", "create", "health", "metrics", "new", "static", "wentu",
+];
+
+lazy_static! {
+    static ref VANITY_SLUG_REGEX: Regex =
+        Regex::new("^[a-z0-9](?:[a-z0-9-]{1,62}[a-z0-9])$").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum SlugStrategy {
+    /// Random base32 ID only, e.g. `k3v7q2mxa4bz6c5d`
+    Opaque,
+    /// Title-derived prefix plus random ID, e.g. `team-offsite-k3v7q2mxa4bz6c5d`
+    Prefixed,
+}
+
+impl FromStr for SlugStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "opaque" => Ok(SlugStrategy::Opaque),
+            "prefixed" => Ok(SlugStrategy::Prefixed),
+            other => Err(format!("unknown slug strategy '{}'", other)),
+        }
+    }
+}
+
+impl TryFrom<String> for SlugStrategy {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Generate a fresh slug; callers retry on collision.
+pub fn generate(strategy: SlugStrategy, title: &str) -> String {
+    let mut bytes = [0u8; RANDOM_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let id = base32(&bytes);
+
+    match strategy {
+        SlugStrategy::Opaque => id,
+        SlugStrategy::Prefixed => {
+            let prefix = slugify(title)
+                .chars()
+                .take(MAX_PREFIX_LEN)
+                .collect::<String>()
+                .trim_end_matches('-')
+                .to_string();
+            if prefix.is_empty() {
+                id
+            } else {
+                format!("{}-{}", prefix, id)
+            }
+        }
+    }
+}
+
+/// Normalize and validate a creator-chosen slug.
+pub fn validate_vanity(slug: &str) -> Option<String> {
+    let slug = slug.trim().to_lowercase();
+    if !VANITY_SLUG_REGEX.is_match(&slug) || slug.contains("--") {
+        return None;
+    }
+    if RESERVED_SLUGS.contains(&slug.as_str()) {
+        return None;
+    }
+    Some(slug)
+}
+
+/// Whether an insert failed because the slug is already taken.
+pub fn is_slug_conflict(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Database(db_err) => {
+            db_err.is_unique_violation() && db_err.constraint() == Some("wentus_slug_key")
+        }
+        _ => false,
+    }
+}
+
+/// ASCII-only slug fragment; accents are stripped and other scripts dropped.
+pub fn slugify(s: &str) -> String {
+    s.nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .collect::<String>()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Unpadded lowercase RFC 4648 base32.
+fn base32(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+
+    for &byte in bytes {
+        buffer = (buffer << 8) | u16::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base32_known_vector() {
+        assert_eq!(base32(b"foobar"), "mzxw6ytboi");
+    }
+
+    #[test]
+    fn test_generated_slugs() {
+        let opaque = generate(SlugStrategy::Opaque, "Team Offsite");
+        assert_eq!(opaque.len(), 16);
+        assert!(!opaque.contains("team"));
+
+        let prefixed = generate(SlugStrategy::Prefixed, "Team Offsite!");
+        assert!(prefixed.starts_with("team-offsite-"));
+        assert!(validate_vanity(&prefixed).is_some());
+
+        assert!(generate(SlugStrategy::Prefixed, "Réunion 王伟").starts_with("reunion-"));
+        assert_eq!(generate(SlugStrategy::Prefixed, "王伟").len(), 16);
+    }
+
+    #[test]
+    fn test_vanity_validation() {
+        assert_eq!(
+            validate_vanity(" Q1-Planning "),
+            Some("q1-planning".to_string())
+        );
+        assert_eq!(validate_vanity("ab"), None);
+        assert_eq!(validate_vanity("-planning"), None);
+        assert_eq!(validate_vanity("q1--planning"), None);
+        assert_eq!(validate_vanity("plan ning"), None);
+        assert_eq!(validate_vanity("api"), None);
+    }
+}
@@ -0,0 +1,304 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{STVResult, STVRound};
+use crate::ballots::Candidate;
+
+/// An option's votes in a round.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Tally {
+    pub option_id: Uuid,
+    pub label: String,
+    pub votes: usize,
+}
+
+/// A round in readable form: labelled tallies, the option eliminated and where its votes went.
+#[derive(Debug, Serialize)]
+pub struct RoundExplanation {
+    pub round_number: usize,
+    /// Options still standing, most votes first
+    pub tallies: Vec<Tally>,
+    pub eliminated: Option<Tally>,
+    /// Votes the eliminated option passed on, by recipient
+    pub transfers: Vec<Tally>,
+    /// Votes of the eliminated option with no further preference
+    pub exhausted: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Explanation {
+    pub summary: String,
+    pub rounds: Vec<RoundExplanation>,
+    /// Summary and rounds as plain text, one line each
+    pub text: String,
+}
+
+fn votes(count: usize) -> String {
+    match count {
+        1 => "1 vote".to_string(),
+        count => format!("{} votes", count),
+    }
+}
+
+/// Labels and display order of the candidates.
+struct Labels<'a> {
+    candidates: &'a [Candidate],
+}
+
+impl Labels<'_> {
+    fn tally(&self, option_id: Uuid, votes: usize) -> Tally {
+        let label = self
+            .candidates
+            .iter()
+            .find(|candidate| candidate.id == option_id)
+            .map_or("Unknown date", |candidate| candidate.label.as_str());
+        Tally {
+            option_id,
+            label: label.to_string(),
+            votes,
+        }
+    }
+
+    fn position(&self, option_id: Uuid) -> usize {
+        self.candidates
+            .iter()
+            .position(|candidate| candidate.id == option_id)
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Most votes first, ties in display order.
+    fn sort(&self, tallies: &mut [Tally]) {
+        tallies.sort_by_key(|tally| {
+            (
+                std::cmp::Reverse(tally.votes),
+                self.position(tally.option_id),
+            )
+        });
+    }
+}
+
+/// Where the eliminated option's votes went, most first.
+fn transfers(labels: &Labels, round: &STVRound) -> Vec<Tally> {
+    let mut transfers: Vec<Tally> = round
+        .transfers
+        .iter()
+        .map(|(&option_id, &count)| labels.tally(option_id, count))
+        .collect();
+    labels.sort(&mut transfers);
+    transfers
+}
+
+fn elimination_text(eliminated: &Tally, transfers: &[Tally], exhausted: usize) -> String {
+    let mut text = format!(
+        "{} eliminated with {}",
+        eliminated.label,
+        votes(eliminated.votes)
+    );
+    match (transfers, exhausted) {
+        (_, _) if eliminated.votes == 0 => {}
+        ([only], 0) => text.push_str(&format!(
+            "; its {} transferred to {}",
+            votes(only.votes),
+            only.label
+        )),
+        ([], exhausted) => text.push_str(&format!(
+            "; {} had no further preference",
+            if exhausted == 1 { "it" } else { "they" }
+        )),
+        (transfers, exhausted) => {
+            let mut parts: Vec<String> = transfers
+                .iter()
+                .map(|tally| format!("{} to {}", tally.votes, tally.label))
+                .collect();
+            if exhausted > 0 {
+                parts.push(format!("{} had no further preference", exhausted));
+            }
+            let last = parts.pop().unwrap_or_default();
+            let list = if parts.is_empty() {
+                last
+            } else {
+                format!("{} and {}", parts.join(", "), last)
+            };
+            text.push_str(&format!("; of its {}, {}", votes(eliminated.votes), list));
+        }
+    }
+    text
+}
+
+/// Describe an STV count round by round with date labels in place of option ids.
+pub fn explain(result: &STVResult, candidates: &[Candidate], total_voters: usize) -> Explanation {
+    let labels = Labels { candidates };
+    let mut rounds = Vec::with_capacity(result.rounds.len());
+
+    for round in &result.rounds {
+        let mut tallies: Vec<Tally> = round
+            .vote_counts
+            .iter()
+            .map(|(&option_id, &count)| labels.tally(option_id, count))
+            .collect();
+        labels.sort(&mut tallies);
+
+        let eliminated = round.eliminated.map(|option_id| {
+            labels.tally(
+                option_id,
+                round.vote_counts.get(&option_id).copied().unwrap_or(0),
+            )
+        });
+        let transfers = transfers(&labels, round);
+
+        let counts = tallies
+            .iter()
+            .map(|tally| format!("{} ({})", tally.label, votes(tally.votes)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let outcome = match (&eliminated, result.winner) {
+            (Some(eliminated), _) => elimination_text(eliminated, &transfers, round.exhausted),
+            (None, Some(winner)) => {
+                let winner =
+                    labels.tally(winner, round.vote_counts.get(&winner).copied().unwrap_or(0));
+                if winner.votes >= result.quota {
+                    format!(
+                        "{} reaches the quota of {} and wins",
+                        winner.label, result.quota
+                    )
+                } else {
+                    format!("{} is the last option left and wins", winner.label)
+                }
+            }
+            (None, None) => "No option wins".to_string(),
+        };
+
+        rounds.push(RoundExplanation {
+            round_number: round.round_number,
+            text: format!("Round {}: {}. {}.", round.round_number, counts, outcome),
+            tallies,
+            eliminated,
+            transfers,
+            exhausted: round.exhausted,
+        });
+    }
+
+    let summary = match (result.winner, result.rounds.last()) {
+        (Some(winner), Some(last)) => {
+            let winner = labels.tally(winner, last.vote_counts.get(&winner).copied().unwrap_or(0));
+            format!(
+                "{} won in round {} with {} of {}.",
+                winner.label,
+                last.round_number,
+                winner.votes,
+                votes(total_voters)
+            )
+        }
+        (_, None) => "No ballots have been cast yet.".to_string(),
+        (None, Some(_)) => "No option won.".to_string(),
+    };
+
+    let text = std::iter::once(summary.as_str())
+        .chain(rounds.iter().map(|round| round.text.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Explanation {
+        summary,
+        rounds,
+        text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn candidate(label: &str) -> Candidate {
+        Candidate {
+            id: Uuid::new_v4(),
+            label: label.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_explain_elimination_and_transfer() {
+        let candidates = vec![
+            candidate("Tue 12th"),
+            candidate("Wed 13th"),
+            candidate("Thu 14th"),
+        ];
+        let [tue, wed, thu] = [0, 1, 2].map(|i| candidates[i].id);
+        let result = STVResult {
+            winner: Some(tue),
+            quota: 3,
+            rounds: vec![
+                STVRound {
+                    round_number: 1,
+                    vote_counts: HashMap::from([(tue, 2), (wed, 1), (thu, 1)]),
+                    eliminated: Some(thu),
+                    transfers: HashMap::from([(tue, 1)]),
+                    exhausted: 0,
+                },
+                STVRound {
+                    round_number: 2,
+                    vote_counts: HashMap::from([(tue, 3), (wed, 1)]),
+                    eliminated: None,
+                    transfers: HashMap::new(),
+                    exhausted: 0,
+                },
+            ],
+        };
+
+        let explanation = explain(&result, &candidates, 4);
+        assert_eq!(
+            explanation.text,
+            "Tue 12th won in round 2 with 3 of 4 votes.\n\
+             Round 1: Tue 12th (2 votes), Wed 13th (1 vote), Thu 14th (1 vote). \
+             Thu 14th eliminated with 1 vote; its 1 vote transferred to Tue 12th.\n\
+             Round 2: Tue 12th (3 votes), Wed 13th (1 vote). Tue 12th reaches the quota of 3 and wins."
+        );
+        assert_eq!(
+            explanation.rounds[0].transfers,
+            vec![Tally {
+                option_id: tue,
+                label: "Tue 12th".to_string(),
+                votes: 1,
+            }]
+        );
+        assert_eq!(explanation.rounds[0].exhausted, 0);
+    }
+
+    #[test]
+    fn test_explain_split_and_exhausted_transfers() {
+        let candidates = vec![candidate("Mon"), candidate("Tue"), candidate("Wed")];
+        let [mon, tue, wed] = [0, 1, 2].map(|i| candidates[i].id);
+        let round = STVRound {
+            round_number: 1,
+            vote_counts: HashMap::from([(mon, 4), (tue, 3), (wed, 3)]),
+            eliminated: Some(wed),
+            transfers: HashMap::from([(tue, 1), (mon, 1)]),
+            exhausted: 1,
+        };
+        let labels = Labels {
+            candidates: &candidates,
+        };
+
+        assert_eq!(
+            elimination_text(
+                &labels.tally(wed, 3),
+                &transfers(&labels, &round),
+                round.exhausted
+            ),
+            "Wed eliminated with 3 votes; of its 3 votes, 1 to Mon, 1 to Tue and 1 had no further preference"
+        );
+
+        let empty = explain(
+            &STVResult {
+                winner: None,
+                rounds: vec![],
+                quota: 0,
+            },
+            &candidates,
+            0,
+        );
+        assert_eq!(empty.text, "No ballots have been cast yet.");
+    }
+}
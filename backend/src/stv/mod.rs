@@ -1,11 +1,17 @@
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+pub mod explain;
+
 #[derive(Debug, Clone)]
 pub struct STVRound {
     pub round_number: usize,
     pub vote_counts: HashMap<Uuid, usize>,
     pub eliminated: Option<Uuid>,
+    /// Where the eliminated option's ballots went next, by receiving option
+    pub transfers: HashMap<Uuid, usize>,
+    /// Ballots of the eliminated option with no preference left
+    pub exhausted: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -53,6 +59,8 @@ pub fn calculate_stv(
                     round_number: rounds.len() + 1,
                     vote_counts: vote_counts.clone(),
                     eliminated: None,
+                    transfers: HashMap::new(),
+                    exhausted: 0,
                 });
                 return STVResult {
                     winner: Some(winner),
@@ -72,6 +80,8 @@ pub fn calculate_stv(
                 round_number: rounds.len() + 1,
                 vote_counts: vote_counts.clone(),
                 eliminated: None,
+                transfers: HashMap::new(),
+                exhausted: 0,
             });
             return STVResult {
                 winner: remaining,
@@ -86,11 +96,27 @@ pub fn calculate_stv(
             .filter(|(&opt, _)| !eliminated.contains(&opt))
             .min_by_key(|&(_, &c)| c)
         {
+            // Follow each of its ballots to the next option still standing
+            let mut transfers: HashMap<Uuid, usize> = HashMap::new();
+            let mut exhausted = 0;
+            for prefs in &current_preferences {
+                let mut standing = prefs.iter().filter(|&&opt| !eliminated.contains(&opt));
+                if standing.next() != Some(&to_eliminate) {
+                    continue;
+                }
+                match standing.next() {
+                    Some(&next_choice) => *transfers.entry(next_choice).or_insert(0) += 1,
+                    None => exhausted += 1,
+                }
+            }
+
             eliminated.insert(to_eliminate);
             rounds.push(STVRound {
                 round_number: rounds.len() + 1,
                 vote_counts: vote_counts.clone(),
                 eliminated: Some(to_eliminate),
+                transfers,
+                exhausted,
             });
         } else {
             break;
@@ -152,4 +178,34 @@ mod tests {
         assert!(result.winner.is_some());
         assert!(!result.rounds.is_empty());
     }
+
+    #[test]
+    fn test_records_transfers() {
+        let uuid1 = Uuid::nil();
+        let uuid2 = Uuid::new_v4();
+        let uuid3 = Uuid::new_v4();
+
+        // uuid3 is eliminated first; one of its ballots moves to uuid1, one is exhausted
+        let preferences = vec![
+            vec![uuid1],
+            vec![uuid1],
+            vec![uuid1],
+            vec![uuid2],
+            vec![uuid2],
+            vec![uuid2],
+            vec![uuid3, uuid1],
+            vec![uuid3],
+        ];
+        let options = vec![uuid1, uuid2, uuid3];
+
+        let result = calculate_stv(preferences, options);
+        let first = &result.rounds[0];
+        assert_eq!(first.eliminated, Some(uuid3));
+        assert_eq!(first.transfers, HashMap::from([(uuid1, 1)]));
+        assert_eq!(first.exhausted, 1);
+
+        let last = result.rounds.last().unwrap();
+        assert!(last.transfers.is_empty());
+        assert_eq!(result.winner, Some(uuid1));
+    }
 }
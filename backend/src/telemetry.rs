@@ -0,0 +1,240 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header::CONTENT_TYPE, HeaderMap, HeaderName, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use serde::Deserialize;
+use std::time::Instant;
+use subtle::ConstantTimeEq;
+use tracing::Span;
+use tracing_subscriber::EnvFilter;
+
+use crate::auth::bearer_token;
+use crate::handlers::AppState;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied request id that is propagated rather than replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+/// Install the global tracing subscriber. `RUST_LOG` filters as usual (default `info`).
+pub fn init_tracing(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt().with_env_filter(filter).init(),
+        LogFormat::Json => tracing_subscriber::fmt()
+            .json()
+            .with_env_filter(filter)
+            .with_current_span(true)
+            .with_span_list(false)
+            .init(),
+    }
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
+/// Drop client-supplied request ids that could garble logs, so a fresh one is generated.
+pub async fn sanitize_request_id(mut req: Request) -> Request {
+    let valid = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .map(|value| value.to_str().is_ok_and(is_valid_request_id));
+    if valid == Some(false) {
+        req.headers_mut().remove(REQUEST_ID_HEADER);
+    }
+    req
+}
+
+/// Root span for a request. Carries the route template, request id and wentu slug;
+/// `participant_id` is filled in once a bearer token has been verified. Query strings and
+/// bodies are never recorded, so keys and invite tokens stay out of the logs.
+pub fn request_span(req: &Request) -> Span {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str)
+        .unwrap_or("unmatched");
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    let span = tracing::info_span!(
+        "request",
+        method = %req.method(),
+        route,
+        request_id,
+        slug = tracing::field::Empty,
+        participant_id = tracing::field::Empty,
+    );
+    if route.starts_with("/api/wentu/:slug") {
+        if let Some(slug) = req.uri().path().split('/').nth(3) {
+            span.record("slug", slug);
+        }
+    }
+    span
+}
+
+/// Buckets (seconds) for request and STV computation latencies.
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Install the global Prometheus recorder; call once at startup.
+pub fn install_recorder() -> Result<PrometheusHandle, BuildError> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Suffix("duration_seconds".to_string()),
+            LATENCY_BUCKETS,
+        )?
+        .install_recorder()
+}
+
+/// Count requests and record their latency, labelled by route template rather than raw
+/// path so slugs do not explode label cardinality.
+pub async fn track_requests(req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(req).await;
+
+    let status = response.status().as_u16().to_string();
+    counter!(
+        "http_requests_total",
+        "method" => method.clone(),
+        "route" => route.clone(),
+        "status" => status,
+    )
+    .increment(1);
+    histogram!(
+        "http_request_duration_seconds",
+        "method" => method,
+        "route" => route,
+    )
+    .record(started.elapsed().as_secs_f64());
+
+    response
+}
+
+/// Record a request rejected by one of the rate limiters.
+pub fn record_rate_limited(limiter: &'static str) {
+    counter!("rate_limit_rejections_total", "limiter" => limiter).increment(1);
+}
+
+/// Record how long one STV tabulation took.
+pub fn record_stv_duration(started: Instant) {
+    histogram!("stv_compute_duration_seconds").record(started.elapsed().as_secs_f64());
+}
+
+/// Record whether a results request was served from the results cache.
+pub fn record_results_cache(hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    counter!("results_cache_requests_total", "result" => result).increment(1);
+}
+
+/// Record wentus deleted by the reaper.
+pub fn record_reaped(count: u64) {
+    counter!("reaper_deleted_wentus_total").increment(count);
+}
+
+/// Refresh gauges that are sampled at scrape time: pool utilization and domain activity.
+async fn refresh_gauges(state: &AppState) -> Result<(), sqlx::Error> {
+    let pool_size = state.db.size();
+    let pool_idle = state.db.num_idle() as u32;
+    gauge!("db_pool_connections").set(pool_size as f64);
+    gauge!("db_pool_idle_connections").set(pool_idle as f64);
+    gauge!("db_pool_in_use_connections").set(pool_size.saturating_sub(pool_idle) as f64);
+    gauge!("db_pool_max_connections").set(state.settings.database_max_connections as f64);
+
+    let (open_wentus, joins_last_hour, ballots_last_hour) = sqlx::query_as::<_, (i64, i64, i64)>(
+        "SELECT
+            (SELECT COUNT(*) FROM wentus WHERE status = 'open' AND expires_at > NOW()),
+            (SELECT COUNT(*) FROM participants
+             WHERE NOT is_creator AND joined_at > NOW() - INTERVAL '1 hour'),
+            (SELECT COUNT(*) FROM audit_logs
+             WHERE action = 'UPDATE_PREFERENCES' AND success
+               AND timestamp > NOW() - INTERVAL '1 hour')",
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    gauge!("wentus_open").set(open_wentus as f64);
+    gauge!("wentu_joins_last_hour").set(joins_last_hour as f64);
+    gauge!("wentu_ballots_last_hour").set(ballots_last_hour as f64);
+    Ok(())
+}
+
+/// Prometheus scrape endpoint. Requires `Authorization: Bearer <METRICS_TOKEN>` when
+/// `METRICS_TOKEN` is set.
+/// Compare a presented bearer token in constant time, so response timing does not reveal how
+/// much of the metrics token a guess got right.
+fn token_matches(presented: Option<&str>, expected: &str) -> bool {
+    presented.is_some_and(|token| bool::from(token.as_bytes().ct_eq(expected.as_bytes())))
+}
+
+pub async fn metrics_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    if let Some(expected) = state.settings.metrics_token.as_deref() {
+        if !token_matches(bearer_token(&headers), expected) {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    if let Err(e) = refresh_gauges(&state).await {
+        // Still serve the request metrics; the domain gauges keep their last values
+        tracing::warn!("Failed to refresh metrics gauges: {:?}", e);
+    }
+
+    Ok((
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches(Some("s3cret-metrics"), "s3cret-metrics"));
+        assert!(!token_matches(Some("s3cret-metricz"), "s3cret-metrics"));
+        assert!(!token_matches(Some("s3cret"), "s3cret-metrics"));
+        assert!(!token_matches(None, "s3cret-metrics"));
+    }
+
+    #[test]
+    fn test_request_id_validation() {
+        assert!(is_valid_request_id("3f2b6c1e-8a4d-4f7e-9b1a-2c3d4e5f6a7b"));
+        assert!(is_valid_request_id("lb.trace-42:7"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("id with spaces"));
+        assert!(!is_valid_request_id("line\nbreak"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+}
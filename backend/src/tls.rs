@@ -0,0 +1,256 @@
+use axum::{
+    http::{header::HOST, uri::Authority, HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Redirect, Response},
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::crypto::{aws_lc_rs, CryptoProvider};
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use rustls::{ServerConfig, SupportedProtocolVersion};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+use crate::health::Heartbeats;
+use crate::settings::Settings;
+use crate::shutdown::Shutdown;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+    #[error("TLS_CERT_PATH and TLS_KEY_PATH are required when ENABLE_HTTPS=true")]
+    MissingPaths,
+    #[error("failed to read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("no certificates found in {0}")]
+    NoCertificates(PathBuf),
+    #[error("no private key found in {0}")]
+    NoPrivateKey(PathBuf),
+    #[error("unknown TLS cipher suite '{0}'")]
+    UnknownCipherSuite(String),
+    #[error("invalid TLS configuration: {0}")]
+    Rustls(#[from] rustls::Error),
+}
+
+/// Protocol versions allowed by `TLS_MIN_VERSION` ("1.2" or "1.3").
+fn protocol_versions(min_version: &str) -> &'static [&'static SupportedProtocolVersion] {
+    static TLS13_ONLY: &[&SupportedProtocolVersion] = &[&rustls::version::TLS13];
+    match min_version {
+        "1.3" => TLS13_ONLY,
+        _ => rustls::ALL_VERSIONS,
+    }
+}
+
+/// Crypto provider restricted to `TLS_CIPHER_SUITES` (rustls names, e.g.
+/// `TLS13_AES_256_GCM_SHA384`); all provider defaults when the list is empty.
+fn crypto_provider(cipher_suites: &str) -> Result<CryptoProvider, TlsError> {
+    let mut provider = aws_lc_rs::default_provider();
+    let wanted: Vec<&str> = cipher_suites
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+    if wanted.is_empty() {
+        return Ok(provider);
+    }
+
+    let available = std::mem::take(&mut provider.cipher_suites);
+    for name in wanted {
+        let suite = available
+            .iter()
+            .find(|suite| format!("{:?}", suite.suite()).eq_ignore_ascii_case(name))
+            .ok_or_else(|| TlsError::UnknownCipherSuite(name.to_string()))?;
+        provider.cipher_suites.push(*suite);
+    }
+    Ok(provider)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, TlsError> {
+    std::fs::read(path).map_err(|e| TlsError::Read(path.to_path_buf(), e))
+}
+
+fn cert_and_key_paths(settings: &Settings) -> Result<(&Path, &Path), TlsError> {
+    match (&settings.tls_cert_path, &settings.tls_key_path) {
+        (Some(cert), Some(key)) => Ok((cert, key)),
+        _ => Err(TlsError::MissingPaths),
+    }
+}
+
+/// Build a rustls server config from the PEM files and TLS settings.
+pub fn load_server_config(settings: &Settings) -> Result<ServerConfig, TlsError> {
+    let (cert_path, key_path) = cert_and_key_paths(settings)?;
+
+    let certs: Vec<CertificateDer<'static>> = CertificateDer::pem_slice_iter(&read(cert_path)?)
+        .collect::<Result<_, _>>()
+        .map_err(|_| TlsError::NoCertificates(cert_path.to_path_buf()))?;
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificates(cert_path.to_path_buf()));
+    }
+    let key = PrivateKeyDer::from_pem_slice(&read(key_path)?)
+        .map_err(|_| TlsError::NoPrivateKey(key_path.to_path_buf()))?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(crypto_provider(
+        &settings.tls_cipher_suites,
+    )?))
+    .with_protocol_versions(protocol_versions(&settings.tls_min_version))?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(config)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn file_stamps(settings: &Settings) -> Option<(SystemTime, SystemTime)> {
+    let (cert_path, key_path) = cert_and_key_paths(settings).ok()?;
+    Some((modified(cert_path)?, modified(key_path)?))
+}
+
+fn reload(config: &RustlsConfig, settings: &Settings) {
+    match load_server_config(settings) {
+        Ok(new_config) => {
+            config.reload_from_config(Arc::new(new_config));
+            tracing::info!("Reloaded TLS certificate");
+        }
+        // Keep serving with the previous certificate
+        Err(e) => tracing::error!("Failed to reload TLS certificate: {}", e),
+    }
+}
+
+/// Reload the certificate on SIGHUP and, when `TLS_RELOAD_INTERVAL_SECS` is set, whenever
+/// the cert or key file's modification time changes.
+pub fn spawn_reloader(
+    config: RustlsConfig,
+    settings: Arc<Settings>,
+    heartbeats: Heartbeats,
+    shutdown: Shutdown,
+) -> JoinHandle<()> {
+    let poll_interval = settings.tls_reload_interval();
+    if let Some(interval) = poll_interval {
+        heartbeats.register("tls_reload", interval);
+    }
+
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        {
+            Ok(signal) => Some(signal),
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGHUP: {:?}", e);
+                None
+            }
+        };
+        // Without polling the tick branch is disabled and this interval is never polled
+        let mut ticker = tokio::time::interval(poll_interval.unwrap_or(Duration::from_secs(3_600)));
+        let mut stamps = file_stamps(&settings);
+
+        loop {
+            #[cfg(unix)]
+            let sighup = async {
+                match hangup.as_mut() {
+                    Some(signal) => {
+                        signal.recv().await;
+                    }
+                    None => std::future::pending().await,
+                }
+            };
+            #[cfg(not(unix))]
+            let sighup = std::future::pending::<()>();
+
+            tokio::select! {
+                _ = sighup => {
+                    tracing::info!("SIGHUP received, reloading TLS certificate");
+                    reload(&config, &settings);
+                    stamps = file_stamps(&settings);
+                }
+                _ = ticker.tick(), if poll_interval.is_some() => {
+                    let current = file_stamps(&settings);
+                    if current.is_some() && current != stamps {
+                        tracing::info!("TLS certificate files changed, reloading");
+                        reload(&config, &settings);
+                        stamps = current;
+                    }
+                    heartbeats.beat("tls_reload");
+                }
+                _ = shutdown.wait() => break,
+            }
+        }
+    })
+}
+
+/// Build the HTTPS URL for a plain HTTP request, keeping host, path and query.
+fn https_location(headers: &HeaderMap, uri: &Uri, https_port: u16) -> Option<String> {
+    let authority: Authority = headers.get(HOST)?.to_str().ok()?.parse().ok()?;
+    let port = match https_port {
+        443 => String::new(),
+        port => format!(":{}", port),
+    };
+    let path = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+    Some(format!("https://{}{}{}", authority.host(), port, path))
+}
+
+async fn redirect(headers: HeaderMap, uri: Uri, https_port: u16) -> Response {
+    match https_location(&headers, &uri, https_port) {
+        // 308 keeps the method and body, so POSTs are retried as POSTs
+        Some(location) => Redirect::permanent(&location).into_response(),
+        None => StatusCode::BAD_REQUEST.into_response(),
+    }
+}
+
+/// Plain HTTP listener that redirects every request to the HTTPS server.
+pub async fn serve_redirect(
+    addr: SocketAddr,
+    https_port: u16,
+    shutdown: Shutdown,
+) -> std::io::Result<()> {
+    let app = Router::new()
+        .fallback(move |headers: HeaderMap, uri: Uri| redirect(headers, uri, https_port));
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("Redirecting http://{} to HTTPS", addr);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { shutdown.wait().await })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_https_location() {
+        let mut headers = HeaderMap::new();
+        headers.insert(HOST, HeaderValue::from_static("wentu.example:80"));
+        let uri: Uri = "/api/wentu/abc?x=1".parse().unwrap();
+
+        assert_eq!(
+            https_location(&headers, &uri, 443).as_deref(),
+            Some("https://wentu.example/api/wentu/abc?x=1")
+        );
+        assert_eq!(
+            https_location(&headers, &uri, 8443).as_deref(),
+            Some("https://wentu.example:8443/api/wentu/abc?x=1")
+        );
+
+        headers.insert(HOST, HeaderValue::from_static("evil.example/path"));
+        assert_eq!(https_location(&headers, &uri, 443), None);
+    }
+
+    #[test]
+    fn test_cipher_suite_selection() {
+        let provider =
+            crypto_provider("TLS13_AES_256_GCM_SHA384, tls13_chacha20_poly1305_sha256").unwrap();
+        assert_eq!(provider.cipher_suites.len(), 2);
+
+        assert!(crypto_provider("").unwrap().cipher_suites.len() > 2);
+        assert!(matches!(
+            crypto_provider("TLS_RSA_WITH_RC4_128_MD5"),
+            Err(TlsError::UnknownCipherSuite(_))
+        ));
+    }
+}
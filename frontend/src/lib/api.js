@@ -11,9 +11,20 @@ function buildUrl(path) {
   return `${BASE_URL}${path}`;
 }
 
+function authHeaders(token, passphrase) {
+  const headers = token ? { Authorization: `Bearer ${token}` } : {};
+  // Private wentus accept their access passphrase in place of a participant token
+  if (passphrase) {
+    headers['X-Wentu-Passphrase'] = passphrase;
+  }
+  return headers;
+}
+
 export const api = {
-  async get(path) {
-    const response = await fetch(buildUrl(path));
+  async get(path, token, passphrase) {
+    const response = await fetch(buildUrl(path), {
+      headers: authHeaders(token, passphrase),
+    });
     if (!response.ok) {
       throw new Error(`HTTP ${response.status}: ${response.statusText}`);
     }
@@ -22,10 +33,10 @@ export const api = {
     return text ? JSON.parse(text) : null;
   },
 
-  async post(path, body) {
+  async post(path, body, token, passphrase) {
     const response = await fetch(buildUrl(path), {
       method: 'POST',
-      headers: { 'Content-Type': 'application/json' },
+      headers: { 'Content-Type': 'application/json', ...authHeaders(token, passphrase) },
       body: JSON.stringify(body),
     });
     if (!response.ok) {
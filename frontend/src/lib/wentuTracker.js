@@ -32,7 +32,7 @@ export function getTrackedWentus() {
  * @param {string} role - Either "owner" or "participant"
  * @param {string} [name] - Participant or creator name to remember
  * @param {string} [participantId] - Participant ID to reuse for updates
- * @param {string} [participantKey] - Participant key to reuse for updates
+ * @param {string} [participantToken] - Participant bearer token to reuse for updates
  */
 export function addTrackedWentu(
   slug,
@@ -40,14 +40,14 @@ export function addTrackedWentu(
   role,
   name = '',
   participantId = '',
-  participantKey = ''
+  participantToken = ''
 ) {
   try {
     const tracked = getTrackedWentus();
     const existingIndex = tracked.findIndex(w => w.slug === slug);
     const trimmedName = typeof name === 'string' ? name.trim() : '';
     const trimmedParticipantId = typeof participantId === 'string' ? participantId.trim() : '';
-    const trimmedParticipantKey = typeof participantKey === 'string' ? participantKey.trim() : '';
+    const trimmedParticipantToken = typeof participantToken === 'string' ? participantToken.trim() : '';
 
     if (existingIndex !== -1) {
       // Update existing entry
@@ -63,8 +63,8 @@ export function addTrackedWentu(
       if (trimmedParticipantId) {
         tracked[existingIndex].participantId = trimmedParticipantId;
       }
-      if (trimmedParticipantKey) {
-        tracked[existingIndex].participantKey = trimmedParticipantKey;
+      if (trimmedParticipantToken) {
+        tracked[existingIndex].participantToken = trimmedParticipantToken;
       }
     } else {
       // Add new entry at the beginning
@@ -75,7 +75,7 @@ export function addTrackedWentu(
         timestamp: Date.now(),
         name: trimmedName || undefined,
         participantId: trimmedParticipantId || undefined,
-        participantKey: trimmedParticipantKey || undefined
+        participantToken: trimmedParticipantToken || undefined
       });
     }
 
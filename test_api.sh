@@ -20,8 +20,12 @@ call_api() {
   local method="$1"
   local path="$2"
   local payload="${3:-}"
+  local token="${4:-}"
 
   local curl_cmd=(curl -s -w $'\n%{http_code}' -X "$method" "$API$path")
+  if [[ -n "$token" ]]; then
+    curl_cmd+=(-H "Authorization: Bearer $token")
+  fi
   if [[ "$method" =~ ^(POST|PUT|PATCH)$ ]]; then
     curl_cmd+=(-H "Content-Type: application/json" -d "$payload")
   fi
@@ -92,8 +96,7 @@ echo
 echo "4️⃣  Joining as participant..."
 PART1=$(call_api POST "/api/wentu/$SLUG/join" '{"name": "Bob"}')
 json_or_note "$PART1"
-PART1_ID=$(echo "$PART1" | jq -r '.participant_id')
-PART1_KEY=$(echo "$PART1" | jq -r '.participant_key')
+PART1_TOKEN=$(echo "$PART1" | jq -r '.token')
 echo "✅ Joined as: Bob"
 throttle_writes
 echo
@@ -101,13 +104,11 @@ echo
 # 5. Submit preferences
 echo "5️⃣  Submitting preferences..."
 PREF1=$(call_api POST "/api/wentu/$SLUG/preferences" "{
-  \"participant_id\": \"$PART1_ID\",
-  \"participant_key\": \"$PART1_KEY\",
   \"rankings\": [
     {\"date_option_id\": \"$DATE1\", \"preference_order\": 1},
     {\"date_option_id\": \"$DATE2\", \"preference_order\": 2}
   ]
-}")
+}" "$PART1_TOKEN")
 json_or_note "$PREF1"
 echo "✅ Preferences submitted"
 throttle_writes
@@ -116,22 +117,23 @@ echo
 # 6. Another participant
 echo "6️⃣  Another participant joins..."
 PART2=$(call_api POST "/api/wentu/$SLUG/join" '{"name": "Charlie"}')
-PART2_ID=$(echo "$PART2" | jq -r '.participant_id')
-PART2_KEY=$(echo "$PART2" | jq -r '.participant_key')
+PART2_TOKEN=$(echo "$PART2" | jq -r '.token')
 throttle_writes
 
 call_api POST "/api/wentu/$SLUG/preferences" "{
-  \"participant_id\": \"$PART2_ID\",
-  \"participant_key\": \"$PART2_KEY\",
   \"rankings\": [
     {\"date_option_id\": \"$DATE2\", \"preference_order\": 1},
     {\"date_option_id\": \"$DATE1\", \"preference_order\": 2}
   ]
-}" >/dev/null
+}" "$PART2_TOKEN" >/dev/null
 echo "✅ Charlie joined and voted"
 throttle_writes
 echo
 
+HAS_VOTED=$(call_api GET "/api/wentu/$SLUG/has-voted" "" "$PART2_TOKEN")
+echo "Charlie has voted: $(echo "$HAS_VOTED" | jq -r '.has_voted')"
+echo
+
 # 7. Get STV results
 echo "7️⃣  Getting STV results..."
 RESULTS=$(call_api GET "/api/wentu/$SLUG/stv-results")